@@ -0,0 +1,321 @@
+use crate::display::format_age;
+use crate::model::Story;
+use anyhow::{Context, Result, anyhow, bail};
+use colored::*;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const CACHE_TTL_SECONDS: u64 = 300; // 5 minutes
+
+/// First word of a cache file's header, followed by the format version and
+/// the time it was saved.
+const CACHE_MAGIC: &str = "hn-stories";
+const CACHE_VERSION: u32 = 3;
+
+/// The cache directory, holding the last listing, one file per listing page
+/// and the HTTP response cache.
+pub fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("hn-cli")
+}
+
+/// The last listing shown, which bare ranks refer to.
+fn get_cache_path() -> PathBuf {
+    cache_dir().join("stories.cache")
+}
+
+/// One page of one listing, keyed by its endpoint (`news`, `ask`, ...).
+fn listing_path(key: &str, page: usize) -> PathBuf {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    cache_dir()
+        .join("listings")
+        .join(format!("{}-{}.cache", name, page))
+}
+
+/// Saves the last listing. `age` is how old the stories already are (see
+/// `save_listing`).
+pub fn save_stories(stories: &[Story], age: Duration) -> Result<()> {
+    write_stories(&get_cache_path(), stories, age)
+}
+
+/// Caches a listing page under its own key and as the last listing. `age` is
+/// how old the stories already are, e.g. when `--offline` served them from
+/// the HTTP cache; they are saved as of then, so they don't look fresh later.
+pub fn save_listing(key: &str, page: usize, stories: &[Story], age: Duration) -> Result<()> {
+    write_stories(&listing_path(key, page), stories, age)?;
+    save_stories(stories, age)
+}
+
+fn write_stories(cache_path: &Path, stories: &[Story], age: Duration) -> Result<()> {
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent).context("Failed to create cache directory")?;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("Failed to get system time")?
+        .as_secs()
+        .saturating_sub(age.as_secs());
+
+    let mut cache_content = format!("{} {} {}\n", CACHE_MAGIC, CACHE_VERSION, timestamp);
+    for story in stories {
+        cache_content.push_str(&story.to_cache_line());
+        cache_content.push('\n');
+    }
+
+    // Hold the lock against other writers of the same listing.
+    let _lock = lock(true)?;
+    write_atomic(cache_path, &cache_content).context("Failed to write cache file")
+}
+
+/// Writes `content` to a temp file next to `path` and renames it over, so
+/// readers never see a half-written file. Each write gets its own temp file,
+/// so concurrent writes of one path in a process can't mix their content.
+pub fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+    let tmp_path = path.with_extension(format!(
+        "tmp{}-{}",
+        std::process::id(),
+        NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = File::create_new(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp_path, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+/// Takes the advisory lock shared by all story caches: exclusive for writers,
+/// shared for readers. It is released when the returned file is dropped.
+fn lock(exclusive: bool) -> Result<File> {
+    let dir = cache_dir();
+    fs::create_dir_all(&dir).context("Failed to create cache directory")?;
+    let file = File::create(dir.join("stories.lock")).context("Failed to open cache lock")?;
+    if exclusive {
+        file.lock()
+    } else {
+        file.lock_shared()
+    }
+    .context("Failed to lock the cache")?;
+    Ok(file)
+}
+
+/// Loads the last listing. With `allow_stale` (offline mode) an expired cache
+/// only produces a warning instead of an error.
+pub fn load_cached_stories(allow_stale: bool) -> Result<Vec<Story>> {
+    read_stories(&get_cache_path(), allow_stale)
+}
+
+/// Loads a listing page saved by `save_listing`, like `load_cached_stories`.
+pub fn load_listing(key: &str, page: usize, allow_stale: bool) -> Result<Vec<Story>> {
+    read_stories(&listing_path(key, page), allow_stale)
+}
+
+fn read_stories(cache_path: &Path, allow_stale: bool) -> Result<Vec<Story>> {
+    let content = {
+        let _lock = lock(false)?;
+        fs::read_to_string(cache_path).context("Failed to read cache file")?
+    };
+    let cache = parse_cache(&content).map_err(|problem| {
+        anyhow!(
+            "Corrupt cache file {}: {}. Run `hn cache clear` or list the stories again.",
+            cache_path.display(),
+            problem
+        )
+    })?;
+
+    if let Some(saved) = cache.saved {
+        let current_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("Failed to get current time")?
+            .as_secs();
+
+        let age = current_time.saturating_sub(saved);
+        if age > CACHE_TTL_SECONDS {
+            if !allow_stale {
+                bail!("Cache expired");
+            }
+            eprintln!(
+                "{} using stories cached {}\n",
+                "Warning:".yellow().bold(),
+                format_age(Duration::from_secs(age))
+            );
+        }
+    }
+
+    if cache.stories.is_empty() {
+        bail!("No stories in cache");
+    }
+
+    Ok(cache.stories)
+}
+
+struct ParsedCache {
+    version: u32,
+    saved: Option<u64>,
+    stories: Vec<Story>,
+}
+
+/// Parses a cache file strictly: any malformed line is an error.
+fn parse_cache(content: &str) -> std::result::Result<ParsedCache, String> {
+    let mut lines = content.lines();
+    let header = lines.next().ok_or("file is empty")?;
+
+    let (version, saved) = match header.split(' ').collect::<Vec<_>>()[..] {
+        [CACHE_MAGIC, version, saved] => {
+            let version = version
+                .parse()
+                .map_err(|_| format!("invalid version '{}'", version))?;
+            if version > CACHE_VERSION {
+                return Err(format!(
+                    "format version {} is newer than this hn (version {})",
+                    version, CACHE_VERSION
+                ));
+            }
+            (version, saved.parse().ok())
+        }
+        // Caches written before the header had a version were just a timestamp.
+        [saved] => (1, Some(saved.parse().map_err(|_| "missing header")?)),
+        _ => return Err("invalid header".to_string()),
+    };
+
+    let stories = lines
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(idx, line)| {
+            Story::from_cache_line(line).ok_or(format!("line {} is malformed", idx + 2))
+        })
+        .collect::<std::result::Result<_, _>>()?;
+
+    Ok(ParsedCache {
+        version,
+        saved,
+        stories,
+    })
+}
+
+/// What `hn cache info` and `hn cache verify` report about one cache file.
+pub struct CacheFileInfo {
+    /// `last` for the last listing, otherwise the listing's file stem.
+    pub name: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub version: Option<u32>,
+    pub saved: Option<SystemTime>,
+    pub stories: usize,
+    pub problem: Option<String>,
+}
+
+/// Inspects every story cache file, last listing first.
+pub fn inspect() -> Result<Vec<CacheFileInfo>> {
+    // Nothing to inspect, and taking the lock would create the directory.
+    if !cache_dir().is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = vec![("last".to_string(), get_cache_path())];
+    if let Ok(entries) = fs::read_dir(cache_dir().join("listings")) {
+        let mut listings: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "cache"))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_string_lossy().into_owned();
+                Some((name, path))
+            })
+            .collect();
+        listings.sort();
+        paths.extend(listings);
+    }
+
+    let _lock = lock(false)?;
+    Ok(paths
+        .into_iter()
+        .filter_map(|(name, path)| {
+            let content = fs::read_to_string(&path);
+            if let Err(e) = &content
+                && e.kind() == io::ErrorKind::NotFound
+            {
+                return None;
+            }
+            let bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let parsed = content
+                .map_err(|e| format!("unreadable: {}", e))
+                .and_then(|content| parse_cache(&content));
+            Some(match parsed {
+                Ok(cache) => CacheFileInfo {
+                    name,
+                    path,
+                    bytes,
+                    version: Some(cache.version),
+                    saved: cache
+                        .saved
+                        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+                    stories: cache.stories.len(),
+                    problem: None,
+                },
+                Err(problem) => CacheFileInfo {
+                    name,
+                    path,
+                    bytes,
+                    version: None,
+                    saved: None,
+                    stories: 0,
+                    problem: Some(problem),
+                },
+            })
+        })
+        .collect())
+}
+
+/// Number and total size of the cached HTTP responses.
+pub fn http_cache_usage() -> (usize, u64) {
+    fs::read_dir(cache_dir().join("http"))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.metadata().ok())
+                .filter(|meta| meta.is_file())
+                .fold((0, 0), |(count, bytes), meta| {
+                    (count + 1, bytes + meta.len())
+                })
+        })
+        .unwrap_or((0, 0))
+}
+
+/// Removes all cached stories and HTTP responses.
+pub fn clear() -> Result<()> {
+    let dir = cache_dir();
+    let _lock = lock(true)?;
+    for name in ["stories.cache", "listings", "http"] {
+        let path = dir.join(name);
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        match removed {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(e).with_context(|| format!("Failed to remove {}", path.display()));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
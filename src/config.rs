@@ -0,0 +1,78 @@
+//! User settings from `~/.config/hn-cli/config`: one `key = value` per line,
+//! `#` starts a comment. Missing file means defaults.
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Page long output when stdout is a terminal.
+    pub pager: bool,
+    /// Pager command line; overrides `$PAGER`.
+    pub pager_command: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            pager: true,
+            pager_command: None,
+        }
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("hn-cli")
+        .join("config")
+}
+
+pub fn load_config() -> Result<Config> {
+    let path = config_path();
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+
+    let mut config = Config::default();
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("{}:{}: expected `key = value`", path.display(), idx + 1);
+        };
+        let value = value.trim();
+        match key.trim() {
+            "pager" => {
+                config.pager = match value {
+                    "true" | "on" | "yes" => true,
+                    "false" | "off" | "no" => false,
+                    _ => bail!(
+                        "{}:{}: pager must be true or false, got '{}'",
+                        path.display(),
+                        idx + 1,
+                        value
+                    ),
+                }
+            }
+            "pager_command" => config.pager_command = Some(value.to_string()),
+            other => bail!(
+                "{}:{}: unknown setting '{}'",
+                path.display(),
+                idx + 1,
+                other
+            ),
+        }
+    }
+
+    Ok(config)
+}
@@ -0,0 +1,54 @@
+//! Minimal CSV writer for exporting listings (RFC 4180 quoting, no extra crate).
+
+use crate::model::Job;
+use anyhow::{Context, Result};
+use chrono::SecondsFormat;
+use std::fs;
+use std::path::Path;
+
+const JOB_HEADER: [&str; 9] = [
+    "rank", "id", "company", "batch", "role", "location", "remote", "posted", "url",
+];
+
+pub fn write_jobs(path: &Path, jobs: &[Job]) -> Result<()> {
+    let mut out = String::new();
+    push_record(&mut out, JOB_HEADER.iter().map(|field| field.to_string()));
+    for job in jobs {
+        let story = &job.story;
+        push_record(
+            &mut out,
+            [
+                story.rank.to_string(),
+                story.id.clone(),
+                job.company.clone().unwrap_or_default(),
+                job.batch.clone().unwrap_or_default(),
+                job.role.clone().unwrap_or_default(),
+                job.location.clone().unwrap_or_default(),
+                job.remote.to_string(),
+                story
+                    .posted
+                    .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+                    .unwrap_or_default(),
+                story.url.clone().unwrap_or_default(),
+            ],
+        );
+    }
+
+    fs::write(path, out).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn push_record(out: &mut String, fields: impl IntoIterator<Item = String>) {
+    for (idx, field) in fields.into_iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(&field);
+        }
+    }
+    out.push_str("\r\n");
+}
@@ -0,0 +1,1034 @@
+use crate::model::{
+    Article, Comment, HiringPost, Item, Job, PollOption, Story, StoryFlag, StoryKind, User,
+};
+use crate::pager::outln;
+use crate::parse::BASE_URL;
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, Utc};
+use clap::ValueEnum;
+use colored::*;
+use std::collections::BTreeSet;
+use std::sync::OnceLock;
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const MAX_PREVIEW_COMMENTS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TimeFormat {
+    /// "3 hours ago"
+    Relative,
+    /// "2024-03-15 14:02" in the local timezone
+    Absolute,
+    /// RFC 3339 with the local offset
+    Iso,
+}
+
+static TIME_FORMAT: OnceLock<TimeFormat> = OnceLock::new();
+
+/// Sets how timestamps are shown for the rest of the process.
+pub fn set_time_format(format: TimeFormat) {
+    let _ = TIME_FORMAT.set(format);
+}
+
+pub fn format_time(time: DateTime<Utc>) -> String {
+    match TIME_FORMAT.get().copied().unwrap_or(TimeFormat::Relative) {
+        TimeFormat::Relative => format_age((Utc::now() - time).to_std().unwrap_or_default()),
+        TimeFormat::Absolute => time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        TimeFormat::Iso => time
+            .with_timezone(&Local)
+            .to_rfc3339_opts(SecondsFormat::Secs, false),
+    }
+}
+
+pub fn display_stories(stories: &[Story]) {
+    for story in stories {
+        let rank = format!("{}.", story.rank);
+        let domain = story
+            .url
+            .as_ref()
+            .map(|u| format!("({})", extract_domain(u)))
+            .unwrap_or_default();
+        let badges = badges(story.kind, &story.flags);
+        let badges_width: usize = badges.iter().map(|b| b.width() + 1).sum();
+        // Keep the title on one line, cut to what's left next to rank, badges
+        // and domain.
+        let title_width = text_width(rank.width() + 1 + badges_width + domain.width() + 1);
+        let mut line = format!(
+            "{} {}",
+            rank.bright_black(),
+            truncate_to_width(without_media_suffix(&story.title), title_width)
+                .bright_white()
+                .bold()
+        );
+        for badge in badges {
+            line.push_str(&format!(" {}", badge));
+        }
+        outln!("{} {}", line, domain.bright_black());
+
+        let mut meta = Vec::new();
+        if let Some(points) = story.points {
+            meta.push(format!("{} points", points).yellow().to_string());
+        }
+        if let Some(author) = &story.author {
+            meta.push(format!("by {}", author).cyan().to_string());
+        }
+        if let Some(posted) = story.posted {
+            meta.push(format_time(posted).bright_black().to_string());
+        }
+        if let Some(comments) = story.comments {
+            meta.push(format!("{} comments", comments).green().to_string());
+        }
+
+        if !meta.is_empty() {
+            outln!("   {}", meta.join(" | "));
+        }
+        outln!();
+    }
+}
+
+/// Badges for a story's kind (unless it's a plain story) and its flags.
+fn badges(kind: StoryKind, flags: &BTreeSet<StoryFlag>) -> Vec<ColoredString> {
+    let mut badges = Vec::new();
+    if kind != StoryKind::Story {
+        badges.push(format!("[{}]", kind.name()).blue());
+    }
+    for &flag in flags {
+        let badge = format!("[{}]", flag.name());
+        badges.push(match flag {
+            StoryFlag::Dead | StoryFlag::Flagged => badge.red(),
+            StoryFlag::Dupe => badge.yellow(),
+            StoryFlag::Pdf | StoryFlag::Video => badge.bright_black(),
+        });
+    }
+    badges
+}
+
+/// The title without its "[pdf]"/"[video]" suffix, which the badge shows.
+fn without_media_suffix(title: &str) -> &str {
+    let title = title.trim_end();
+    let lower = title.to_ascii_lowercase();
+    ["[pdf]", "[video]"]
+        .into_iter()
+        .find(|suffix| lower.ends_with(suffix))
+        .map_or(title, |suffix| {
+            title[..title.len() - suffix.len()].trim_end()
+        })
+}
+
+pub fn display_jobs(jobs: &[Job]) {
+    for job in jobs {
+        let story = &job.story;
+        let mut heading = vec![story.rank.to_string().bright_black().to_string() + "."];
+        match &job.company {
+            Some(company) => heading.push(company.bright_white().bold().to_string()),
+            None => heading.push(story.title.bright_white().bold().to_string()),
+        }
+        if let Some(batch) = &job.batch {
+            heading.push(format!("(YC {})", batch).yellow().to_string());
+        }
+        outln!("{}", heading.join(" "));
+
+        if job.company.is_some()
+            && let Some(role) = &job.role
+        {
+            outln!("   {}", role);
+        }
+
+        let mut meta = Vec::new();
+        if let Some(location) = &job.location {
+            meta.push(location.cyan().to_string());
+        }
+        if job.remote && !job.location.as_deref().is_some_and(is_remote_text) {
+            meta.push("remote".green().to_string());
+        }
+        if let Some(posted) = story.posted {
+            meta.push(format_time(posted).bright_black().to_string());
+        }
+        if let Some(url) = &story.url {
+            meta.push(extract_domain(url).bright_black().to_string());
+        }
+        if !meta.is_empty() {
+            outln!("   {}", meta.join(" | "));
+        }
+        outln!();
+    }
+}
+
+fn is_remote_text(text: &str) -> bool {
+    text.to_ascii_lowercase().contains("remote")
+}
+
+/// Shows an item with a preview of its first comments drawn as a tree.
+/// Replies deeper than `max_depth` are collapsed into a "[+N replies
+/// hidden]" marker; with `highlight_op`, comments by the submitter are marked.
+pub fn display_item(item: &Item, highlight_op: bool, max_depth: Option<usize>) {
+    display_item_header(item);
+
+    if !item.poll.is_empty() {
+        display_poll(&item.poll);
+    }
+
+    let comment_count = item.comments.len();
+
+    if comment_count == 0 {
+        outln!("{}", "No comments yet".bright_black());
+        return;
+    }
+
+    outln!(
+        "{} {}\n",
+        "Comments:".bright_cyan().bold(),
+        format!("({} total)", comment_count).bright_black()
+    );
+
+    // Each visible comment with the number of replies collapsed below it.
+    let mut visible: Vec<(&Comment, usize)> = Vec::new();
+    for comment in &item.comments {
+        match (max_depth, visible.last_mut()) {
+            (Some(max), Some((_, hidden))) if comment.depth > max => *hidden += 1,
+            _ => visible.push((comment, 0)),
+        }
+    }
+    visible.truncate(MAX_PREVIEW_COMMENTS);
+
+    let op = item.author.as_deref().filter(|_| highlight_op);
+    let last_sibling = last_siblings(visible.iter().map(|(c, _)| c.depth));
+    let mut guides: Vec<bool> = Vec::new();
+    let mut shown = 0;
+    for (idx, &(comment, hidden)) in visible.iter().enumerate() {
+        let depth = comment.depth;
+        guides.truncate(depth);
+        guides.resize(depth, true);
+        guides.push(last_sibling[idx]);
+
+        let has_children = hidden > 0
+            || visible
+                .get(idx + 1)
+                .is_some_and(|(next, _)| next.depth > depth);
+        let header = tree_prefix(&guides, depth, true);
+        let mut body = tree_prefix(&guides, depth, false);
+        body.push_str(&if has_children {
+            "│ ".color(depth_color(depth + 1)).to_string()
+        } else {
+            "  ".to_string()
+        });
+
+        print_comment(comment, &header, &body, depth * 3 + 2, op, None);
+
+        if hidden > 0 {
+            let mut marker_guides = guides.clone();
+            marker_guides.push(true);
+            outln!(
+                "{}{}",
+                tree_prefix(&marker_guides, depth + 1, true),
+                format!("[+{} replies hidden]", hidden).bright_black()
+            );
+            outln!("{}", tree_prefix(&guides, depth, false));
+        }
+        shown += 1 + hidden;
+    }
+
+    if comment_count > shown {
+        outln!(
+            "{}",
+            format!("... {} more comments", comment_count - shown).bright_black()
+        );
+    }
+}
+
+const DEPTH_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+];
+
+fn depth_color(depth: usize) -> Color {
+    DEPTH_COLORS[depth % DEPTH_COLORS.len()]
+}
+
+/// For comments in page order, whether each is the last reply to its parent.
+fn last_siblings(depths: impl DoubleEndedIterator<Item = usize>) -> Vec<bool> {
+    // `later[d]`: a later comment at depth `d` shares the current parent.
+    let mut later: Vec<bool> = Vec::new();
+    let mut last: Vec<bool> = depths
+        .rev()
+        .map(|depth| {
+            later.resize(depth + 1, false);
+            let is_last = !later[depth];
+            later[depth] = true;
+            is_last
+        })
+        .collect();
+    last.reverse();
+    last
+}
+
+/// Tree guides for a line at `depth`. `guides[level]` is true when the
+/// comment at that level of the chain is the last reply to its parent; on a
+/// comment's first line the innermost level gets a branch instead of a line.
+fn tree_prefix(guides: &[bool], depth: usize, branch: bool) -> String {
+    let mut prefix = String::new();
+    for level in 1..depth {
+        let last = guides.get(level).copied().unwrap_or(true);
+        prefix.push_str(&if last {
+            "   ".to_string()
+        } else {
+            "│  ".color(depth_color(level)).to_string()
+        });
+    }
+    if depth > 0 {
+        let last = guides.get(depth).copied().unwrap_or(true);
+        let segment = match (branch, last) {
+            (true, true) => "└─ ",
+            (true, false) => "├─ ",
+            (false, true) => "   ",
+            (false, false) => "│  ",
+        };
+        prefix.push_str(&segment.color(depth_color(depth)).to_string());
+    }
+    prefix
+}
+
+/// Shows the comments at `matches` (indices into `item.comments`), each
+/// below its chain of parent comments shortened to one line.
+pub fn display_comment_matches(
+    item: &Item,
+    matches: &[usize],
+    grep: Option<&str>,
+    highlight_op: bool,
+) {
+    display_item_header(item);
+
+    outln!(
+        "{} {}\n",
+        "Matching comments:".bright_cyan().bold(),
+        format!("({} of {})", matches.len(), item.comments.len()).bright_black()
+    );
+
+    let op = item.author.as_deref().filter(|_| highlight_op);
+    for (n, &idx) in matches.iter().enumerate() {
+        if n > 0 {
+            outln!("{}\n", "─".repeat(40).bright_black());
+        }
+
+        for parent in item.ancestors(idx) {
+            let indent = "  ".repeat(parent.depth);
+            let width = text_width(parent.depth * 2 + 2);
+            let lines = wrap_text(&parent.text, width);
+            let mut first_line = lines.first().cloned().unwrap_or_default();
+            if lines.len() > 1 {
+                first_line.push('…');
+            }
+            outln!(
+                "{}{} {} {}",
+                indent,
+                "○".bright_black(),
+                comment_author(parent, op),
+                first_line.bright_black()
+            );
+        }
+        let comment = &item.comments[idx];
+        let indent = "  ".repeat(comment.depth);
+        print_comment(
+            comment,
+            &indent,
+            &format!("{}  ", indent),
+            comment.depth * 2 + 2,
+            op,
+            grep,
+        );
+    }
+}
+
+fn display_item_header(item: &Item) {
+    if !item.title.is_empty() {
+        let mut title = without_media_suffix(&item.title)
+            .bright_white()
+            .bold()
+            .to_string();
+        for badge in badges(item.kind, &item.flags) {
+            title.push_str(&format!(" {}", badge));
+        }
+        if let Some(story_url) = &item.url {
+            outln!("{}", title);
+            outln!(
+                "{} {}\n",
+                "Link:".bright_cyan(),
+                ansi_link(story_url, story_url)
+            );
+        } else {
+            outln!("{}\n", title);
+        }
+    }
+
+    // Wrapped here rather than by the terminal, so the pager counts the
+    // rows a long text really takes.
+    if let Some(text) = &item.text {
+        for line in wrap_text(text, text_width(0)) {
+            outln!("{}", line);
+        }
+        outln!();
+    }
+}
+
+/// Prints a comment below `header` (ending where the dot goes), each text
+/// line after `body`, which is `body_width` columns wide on screen.
+fn print_comment(
+    comment: &Comment,
+    header: &str,
+    body: &str,
+    body_width: usize,
+    op: Option<&str>,
+    grep: Option<&str>,
+) {
+    outln!(
+        "{}{} {} {}",
+        header,
+        "●".color(depth_color(comment.depth)),
+        comment_author(comment, op),
+        comment
+            .posted
+            .map(format_time)
+            .unwrap_or_default()
+            .bright_black()
+    );
+
+    for line in wrap_text(&comment.text, text_width(body_width)) {
+        match grep {
+            Some(grep) => outln!("{}{}", body, highlight(&line, grep)),
+            None => outln!("{}{}", body, line),
+        }
+    }
+
+    outln!("{}", body.trim_end());
+}
+
+fn comment_author(comment: &Comment, op: Option<&str>) -> String {
+    let author = comment.author.as_deref().unwrap_or("[deleted]");
+    if op.is_some() && comment.author.as_deref() == op {
+        format!("{} {}", author.bright_magenta().bold(), "[OP]".magenta())
+    } else {
+        author.cyan().to_string()
+    }
+}
+
+/// Marks every case-insensitive occurrence of `needle` in `line`.
+fn highlight(line: &str, needle: &str) -> String {
+    let lower = line.to_lowercase();
+    let needle = needle.to_lowercase();
+    // Lowercasing can change byte lengths outside ASCII; leave those lines alone.
+    if needle.is_empty() || lower.len() != line.len() {
+        return line.to_string();
+    }
+
+    let mut out = String::new();
+    let mut last = 0;
+    for (start, _) in lower.match_indices(&needle) {
+        out.push_str(&line[last..start]);
+        let end = start + needle.len();
+        out.push_str(&line[start..end].black().on_yellow().to_string());
+        last = end;
+    }
+    out.push_str(&line[last..]);
+    out
+}
+
+/// Renders poll options as a horizontal bar chart scaled to the top option.
+fn display_poll(options: &[PollOption]) {
+    let width = text_width(0);
+    const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+    let total: usize = options.iter().filter_map(|o| o.points).sum();
+    let max = options.iter().filter_map(|o| o.points).max().unwrap_or(0);
+    let label_width = options
+        .iter()
+        .map(|o| o.text.width())
+        .max()
+        .unwrap_or(0)
+        .min(width / 3);
+    let bar_width = width.saturating_sub(label_width + 22).max(10);
+
+    outln!("{}\n", "Poll:".bright_cyan().bold());
+    for option in options {
+        let label = pad_to_width(&truncate_to_width(&option.text, label_width), label_width);
+
+        let points = option.points.unwrap_or(0);
+        // Keep a sliver for options with any votes so they don't look empty.
+        let eighths = (points * bar_width * 8)
+            .checked_div(max)
+            .unwrap_or(0)
+            .max(usize::from(points > 0));
+        let bar = format!("{}{}", "█".repeat(eighths / 8), EIGHTHS[eighths % 8]);
+        let padding = " ".repeat(bar_width - eighths.div_ceil(8));
+        let percent = if total > 0 {
+            points as f64 * 100.0 / total as f64
+        } else {
+            0.0
+        };
+
+        outln!(
+            "  {}  {}{}  {}",
+            label,
+            bar.bright_green(),
+            padding,
+            format!("{} ({:.1}%)", points, percent).bright_black()
+        );
+    }
+    outln!();
+}
+
+/// Shows an extracted article under the story's title.
+pub fn display_article(title: &str, article: &Article) {
+    // Long lines are hard to read, even on a wide terminal.
+    const MAX_WIDTH: usize = 100;
+
+    outln!("{}", title.bright_white().bold());
+    outln!("{}", ansi_link(&article.url, &article.url));
+    outln!(
+        "{}\n",
+        format!(
+            "{} words · {} min read",
+            article.word_count(),
+            article.reading_minutes()
+        )
+        .bright_black()
+    );
+
+    for line in wrap_text(&article.text, text_width(0).min(MAX_WIDTH)) {
+        outln!("{}", line);
+    }
+    outln!();
+}
+
+/// Shows hiring thread posts with their header fields; the excerpt is the
+/// start of the post, or the lines mentioning `grep` when searching.
+pub fn display_hiring_posts(posts: &[HiringPost], grep: Option<&str>) {
+    for post in posts {
+        let comment = &post.comment;
+        let mut heading = Vec::new();
+        match &post.company {
+            Some(company) => heading.push(company.bright_white().bold().to_string()),
+            None => heading.push(
+                comment
+                    .author
+                    .as_deref()
+                    .unwrap_or("[deleted]")
+                    .bright_white()
+                    .bold()
+                    .to_string(),
+            ),
+        }
+        if let Some(role) = &post.role {
+            heading.push(role.to_string());
+        }
+        outln!("{} {}", "●".bright_black(), heading.join(" | "));
+
+        let mut meta = Vec::new();
+        if let Some(location) = &post.location {
+            meta.push(location.cyan().to_string());
+        }
+        if post.remote && !post.location.as_deref().is_some_and(is_remote_text) {
+            meta.push("REMOTE".green().to_string());
+        }
+        if post.onsite {
+            meta.push("ONSITE".yellow().to_string());
+        }
+        if post.visa {
+            meta.push("VISA".magenta().to_string());
+        }
+        if !meta.is_empty() {
+            outln!("  {}", meta.join(" | "));
+        }
+        if let Some(url) = &post.url {
+            outln!("  {}", ansi_link(url, url));
+        }
+
+        let body = comment
+            .text
+            .lines()
+            .skip(1)
+            .filter(|l| !l.trim().is_empty());
+        let excerpt: Vec<&str> = match grep {
+            Some(grep) => {
+                let grep = grep.to_lowercase();
+                body.filter(|l| l.to_lowercase().contains(&grep))
+                    .take(3)
+                    .collect()
+            }
+            None => body.take(2).collect(),
+        };
+        for line in wrap_text(&excerpt.join(" "), text_width(2))
+            .into_iter()
+            .take(4)
+        {
+            outln!("  {}", line);
+        }
+
+        let mut footer = vec![format!("{}/item?id={}", BASE_URL, comment.id)];
+        if let Some(author) = &comment.author {
+            footer.insert(0, format!("by {}", author));
+        }
+        if let Some(posted) = comment.posted {
+            footer.insert(footer.len() - 1, format_time(posted));
+        }
+        outln!("  {}\n", footer.join(" | ").bright_black());
+    }
+}
+
+/// Shows comments from a listing page, each with the story it belongs to.
+pub fn display_comment_list(comments: &[Comment]) {
+    for comment in comments {
+        let mut header = vec![
+            comment
+                .author
+                .as_deref()
+                .unwrap_or("[deleted]")
+                .cyan()
+                .to_string(),
+        ];
+        if let Some(posted) = comment.posted {
+            header.push(format_time(posted).bright_black().to_string());
+        }
+        if let Some(title) = &comment.story_title {
+            header.push(format!("on: {}", title).bright_white().to_string());
+        }
+        outln!("{} {}", "●".bright_black(), header.join(" "));
+
+        if let Some(story_id) = &comment.story_id {
+            outln!(
+                "  {}",
+                format!("{}/item?id={}", BASE_URL, story_id).bright_black()
+            );
+        }
+
+        for line in wrap_text(&comment.text, text_width(2)) {
+            outln!("  {}", line);
+        }
+        outln!();
+    }
+}
+
+pub fn display_user(user: &User) {
+    outln!(
+        "{} {}\n",
+        "Profile:".bright_cyan().bold(),
+        user.id.bright_white()
+    );
+
+    outln!("{}: {}", "Username".bright_yellow(), user.id.bright_white());
+    if let Some(created) = user.created {
+        outln!(
+            "{}: {} {}",
+            "Created".bright_yellow(),
+            created.format("%B %-d, %Y").to_string().bright_white(),
+            format!("({})", account_age(created)).bright_black()
+        );
+    }
+    if let Some(karma) = user.karma {
+        outln!(
+            "{}: {}",
+            "Karma".bright_yellow(),
+            karma.to_string().bright_white()
+        );
+    }
+    if let Some(submissions) = user.submissions {
+        outln!(
+            "{}: {}",
+            "Submissions".bright_yellow(),
+            submissions.to_string().bright_white()
+        );
+    }
+    if let Some(comments) = user.comments {
+        outln!(
+            "{}: {}",
+            "Comments".bright_yellow(),
+            comments.to_string().bright_white()
+        );
+    }
+    if let Some(about) = &user.about {
+        outln!("{}:", "About".bright_yellow());
+        for line in wrap_text(about, text_width(2)) {
+            if line.is_empty() {
+                outln!();
+            } else {
+                outln!("  {}", line.bright_white());
+            }
+        }
+    }
+
+    outln!();
+}
+
+/// Shows several profiles as columns of one table.
+pub fn display_users_side_by_side(users: &[User]) {
+    let unknown = || "-".to_string();
+    let mut rows: Vec<(&str, Vec<String>)> = vec![
+        (
+            "Created",
+            users
+                .iter()
+                .map(|u| u.created.map_or_else(unknown, |d| d.to_string()))
+                .collect(),
+        ),
+        (
+            "Account age",
+            users
+                .iter()
+                .map(|u| u.created.map_or_else(unknown, account_age))
+                .collect(),
+        ),
+        (
+            "Karma",
+            users
+                .iter()
+                .map(|u| u.karma.map_or_else(unknown, |k| k.to_string()))
+                .collect(),
+        ),
+        (
+            "Karma/year",
+            users
+                .iter()
+                .map(|u| match (u.karma, u.created) {
+                    (Some(karma), Some(created)) => {
+                        format!("{:.0}", karma as f64 / years_since(created).max(1.0 / 12.0))
+                    }
+                    _ => unknown(),
+                })
+                .collect(),
+        ),
+        (
+            "Submissions",
+            users
+                .iter()
+                .map(|u| u.submissions.map_or_else(unknown, |n| n.to_string()))
+                .collect(),
+        ),
+        (
+            "Comments",
+            users
+                .iter()
+                .map(|u| u.comments.map_or_else(unknown, |n| n.to_string()))
+                .collect(),
+        ),
+    ];
+    rows.retain(|(_, values)| values.iter().any(|v| v != "-"));
+
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = users
+        .iter()
+        .enumerate()
+        .map(|(idx, user)| {
+            rows.iter()
+                .map(|(_, values)| values[idx].chars().count())
+                .chain([user.id.chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let header: Vec<String> = users
+        .iter()
+        .zip(&widths)
+        .map(|(user, width)| {
+            format!("{:>width$}", user.id)
+                .bright_white()
+                .bold()
+                .to_string()
+        })
+        .collect();
+    outln!("{:label_width$}  {}", "", header.join("  "));
+
+    for (label, values) in &rows {
+        let cells: Vec<String> = values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:>width$}", value))
+            .collect();
+        outln!(
+            "{}  {}",
+            format!("{:label_width$}", label).bright_yellow(),
+            cells.join("  ")
+        );
+    }
+    outln!();
+}
+
+fn years_since(date: NaiveDate) -> f64 {
+    (Local::now().date_naive() - date).num_days() as f64 / 365.25
+}
+
+fn account_age(created: NaiveDate) -> String {
+    let years = years_since(created);
+    if years >= 1.0 {
+        format!("{:.1} years", years)
+    } else {
+        format!("{} days", (Local::now().date_naive() - created).num_days())
+    }
+}
+
+/// Formats an elapsed duration as "N minutes ago".
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    let (value, unit) = match secs {
+        0..60 => return "just now".to_string(),
+        60..3600 => (secs / 60, "minute"),
+        3600..86400 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+    format!(
+        "{} {}{} ago",
+        value,
+        unit,
+        if value == 1 { "" } else { "s" }
+    )
+}
+
+fn extract_domain(url: &str) -> &str {
+    url.split("://")
+        .nth(1)
+        .and_then(|s| s.split('/').next())
+        .unwrap_or(url)
+}
+
+/// Columns left for text after `indent`, based on the terminal's width.
+fn text_width(indent: usize) -> usize {
+    const MIN_WIDTH: usize = 20;
+    let columns = terminal_size::terminal_size()
+        .map(|(width, _)| usize::from(width.0))
+        .unwrap_or(80);
+    columns.saturating_sub(indent).max(MIN_WIDTH)
+}
+
+/// Wraps text to `width` terminal columns. Line breaks in the text are
+/// kept, indented lines (code) keep their indentation, and words wider than
+/// a line are split, preferring a break after URL punctuation.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+
+    for source in text.lines() {
+        let source = expand_tabs(source);
+        let source = source.as_str();
+        if source.trim().is_empty() {
+            lines.push(String::new());
+            continue;
+        }
+
+        let indent = &source[..source.len() - source.trim_start().len()];
+        let indent = if indent.width() < width / 2 {
+            indent
+        } else {
+            ""
+        };
+        let mut line = String::from(indent);
+        let mut line_width = indent.width();
+
+        for word in source.split_whitespace() {
+            let word_width = word.width();
+            let fits = line_width + usize::from(line_width > indent.width()) + word_width <= width;
+            if !fits && line_width > indent.width() {
+                lines.push(std::mem::replace(&mut line, indent.to_string()));
+                line_width = indent.width();
+            }
+
+            if line_width + word_width <= width {
+                if line_width > indent.width() {
+                    line.push(' ');
+                    line_width += 1;
+                }
+                line.push_str(word);
+                line_width += word_width;
+                continue;
+            }
+
+            let mut pieces = split_word(word, width - indent.width())
+                .into_iter()
+                .peekable();
+            while let Some(piece) = pieces.next() {
+                line.push_str(&piece);
+                line_width += piece.width();
+                if pieces.peek().is_some() {
+                    lines.push(std::mem::replace(&mut line, indent.to_string()));
+                    line_width = indent.width();
+                }
+            }
+        }
+
+        if line_width > indent.width() {
+            lines.push(line);
+        }
+    }
+
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Replaces tabs with spaces up to the next tab stop; terminals show them
+/// several columns wide, but they have no width of their own.
+fn expand_tabs(line: &str) -> String {
+    const TAB_WIDTH: usize = 4;
+    let mut out = String::with_capacity(line.len());
+    let mut column = 0;
+    for grapheme in line.graphemes(true) {
+        if grapheme == "\t" {
+            let spaces = TAB_WIDTH - column % TAB_WIDTH;
+            out.push_str(&" ".repeat(spaces));
+            column += spaces;
+        } else {
+            out.push_str(grapheme);
+            column += grapheme.width();
+        }
+    }
+    out
+}
+
+/// Splits a word wider than `width` into pieces that fit. A piece ends after
+/// the last `/`, `-`, `?`, `&`, `=` or `.` in its second half if there is
+/// one, and otherwise at a grapheme boundary.
+fn split_word(word: &str, width: usize) -> Vec<String> {
+    const BREAK_AFTER: [&str; 6] = ["/", "-", "?", "&", "=", "."];
+
+    let width = width.max(1);
+    let mut pieces = Vec::new();
+    let mut piece: Vec<&str> = Vec::new();
+    let mut piece_width = 0;
+
+    for grapheme in word.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if piece_width + grapheme_width > width && !piece.is_empty() {
+            let split = piece
+                .iter()
+                .rposition(|g| BREAK_AFTER.contains(g))
+                .filter(|&pos| pos + 1 >= piece.len() / 2 && pos + 1 < piece.len())
+                .map_or(piece.len(), |pos| pos + 1);
+            let rest = piece.split_off(split);
+            pieces.push(piece.concat());
+            piece_width = rest.iter().map(|g| g.width()).sum();
+            piece = rest;
+        }
+        piece.push(grapheme);
+        piece_width += grapheme_width;
+    }
+
+    if !piece.is_empty() {
+        pieces.push(piece.concat());
+    }
+    pieces
+}
+
+/// Shortens `text` to at most `width` terminal columns, ending with "…"
+/// when something was cut.
+fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut out = String::new();
+    let mut out_width = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if out_width + grapheme_width + 1 > width {
+            break;
+        }
+        out.push_str(grapheme);
+        out_width += grapheme_width;
+    }
+    let mut out = out.trim_end().to_string();
+    out.push('…');
+    out
+}
+
+/// Pads `text` with spaces to `width` terminal columns.
+fn pad_to_width(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(text.width())))
+}
+
+fn ansi_link(url: &str, text: &str) -> String {
+    format!(
+        "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+        url,
+        text.cyan().underline()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widths(lines: &[String]) -> Vec<usize> {
+        lines.iter().map(|line| line.width()).collect()
+    }
+
+    #[test]
+    fn wraps_wide_characters_by_columns() {
+        // Each CJK character takes two columns.
+        let lines = wrap_text("日本語のテキストを折り返す", 10);
+        assert_eq!(lines, ["日本語のテ", "キストを折", "り返す"]);
+        assert_eq!(widths(&lines), [10, 10, 6]);
+    }
+
+    #[test]
+    fn keeps_graphemes_whole() {
+        let family = "👩\u{200d}👩\u{200d}👧\u{200d}👦";
+        assert_eq!(family.width(), 2);
+        let pieces = split_word(&family.repeat(3), 4);
+        assert_eq!(pieces, [family.repeat(2), family.to_string()]);
+
+        // "e" plus a combining acute accent is one column.
+        let word = "cafe\u{301}".repeat(3);
+        assert_eq!(word.width(), 12);
+        let pieces = split_word(&word, 5);
+        assert_eq!(pieces, ["cafe\u{301}c", "afe\u{301}ca", "fe\u{301}"]);
+        assert_eq!(widths(&pieces), [5, 5, 2]);
+    }
+
+    #[test]
+    fn hard_splits_long_urls_after_punctuation() {
+        let url = "https://example.com/a-very-long-path/with/many/segments?page=2";
+        let lines = wrap_text(&format!("See {} for details", url), 24);
+        assert_eq!(
+            lines,
+            [
+                "See",
+                "https://example.com/a-",
+                "very-long-path/with/",
+                "many/segments?page=2 for",
+                "details"
+            ]
+        );
+        assert!(lines.iter().all(|line| line.width() <= 24));
+    }
+
+    #[test]
+    fn indents_continuation_lines() {
+        let lines = wrap_text("    let total = first + second + third;", 20);
+        assert_eq!(
+            lines,
+            ["    let total =", "    first + second +", "    third;"]
+        );
+    }
+
+    #[test]
+    fn expands_tabs_before_measuring() {
+        let lines = wrap_text("\tfn main() { println!(); }", 16);
+        assert_eq!(lines, ["    fn main() {", "    println!();", "    }"]);
+        assert!(lines.iter().all(|line| line.width() <= 16));
+    }
+
+    #[test]
+    fn truncates_by_columns() {
+        assert_eq!(truncate_to_width("short", 10), "short");
+        assert_eq!(truncate_to_width("日本語のテキスト", 7), "日本語…");
+        assert_eq!(truncate_to_width("cafe\u{301} au lait", 6), "cafe\u{301}…");
+        assert_eq!(pad_to_width("日本", 6).width(), 6);
+    }
+}
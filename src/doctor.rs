@@ -0,0 +1,230 @@
+//! `hn doctor`: checks what hn depends on (HN's markup, the network, the
+//! cache and config files) and prints a plain-text report that can be pasted
+//! into a bug report as is.
+
+use crate::cache;
+use crate::config;
+use crate::http_cache::CacheMode;
+use crate::pager::outln;
+use crate::parse::{self, BASE_URL};
+use crate::scraper::HnScraper;
+use anyhow::Result;
+use chrono::Utc;
+use std::time::Instant;
+
+/// Listing pages to check, all parsed by `parse_stories`.
+const LISTINGS: [&str; 5] = ["news", "newest", "ask", "show", "jobs"];
+/// A profile that has existed since HN's first day.
+const KNOWN_USER: &str = "pg";
+
+/// Selectors every listing page must match.
+const LISTING_SELECTORS: [&str; 4] = [
+    "tr.athing",
+    "tr > td.subtext",
+    "span.titleline > a",
+    "span.age",
+];
+/// Selectors every item page with comments must match.
+const ITEM_SELECTORS: [&str; 6] = [
+    "span.titleline > a",
+    "tr > td.subtext",
+    "tr.athing.comtr",
+    "span.comhead",
+    "div.commtext",
+    "td.ind",
+];
+
+pub async fn run(scraper: &HnScraper, mode: CacheMode) -> Result<()> {
+    let mut problems: Vec<String> = Vec::new();
+
+    outln!("hn doctor report");
+    outln!(
+        "version:  hn {} ({} {})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    outln!("date:     {}", Utc::now().format("%Y-%m-%d %H:%M UTC"));
+    outln!(
+        "mode:     {}",
+        match mode {
+            CacheMode::Offline => "offline (pages come from the HTTP cache)",
+            _ => "online",
+        }
+    );
+
+    outln!("\n[files]");
+    check_files(&mut problems);
+
+    outln!("\n[network]");
+    let start = Instant::now();
+    let front = scraper.fetch_page("news").await;
+    match &front {
+        _ if mode == CacheMode::Offline => outln!("not checked (--offline)"),
+        Ok(html) => outln!(
+            "{}: reachable ({} bytes in {} ms)",
+            BASE_URL,
+            html.len(),
+            start.elapsed().as_millis()
+        ),
+        Err(e) => {
+            outln!("{}: unreachable: {:#}", BASE_URL, e);
+            problems.push(format!("{} is unreachable", BASE_URL));
+        }
+    }
+
+    let mut first_story = None;
+    for endpoint in LISTINGS {
+        let html = match (endpoint, &front) {
+            ("news", Ok(html)) => Ok(html.clone()),
+            _ => scraper.fetch_page(endpoint).await,
+        };
+        outln!("\n[{}]", endpoint);
+        let html = match html {
+            Ok(html) => html,
+            Err(e) => {
+                outln!("not fetched: {:#}", e);
+                continue;
+            }
+        };
+
+        let stories = parse::parse_stories(&html, 1);
+        outln!("{:<22} {}", "parsed stories", stories.len());
+        let counts = parse::selector_counts(&html);
+        report_counts(&counts, &LISTING_SELECTORS, endpoint, &mut problems);
+
+        let rows = count(&counts, "tr.athing") - count(&counts, "tr.athing.comtr");
+        let subtexts = count(&counts, "tr > td.subtext");
+        if rows != subtexts {
+            outln!(
+                "mismatch: {} story rows but {} subtext rows",
+                rows,
+                subtexts
+            );
+            problems.push(format!(
+                "{}: {} story rows but {} subtext rows",
+                endpoint, rows, subtexts
+            ));
+        }
+        if stories.is_empty() {
+            problems.push(format!("{}: no stories parsed", endpoint));
+        }
+        if first_story.is_none() {
+            first_story = stories
+                .into_iter()
+                .find(|s| s.comments.is_some_and(|c| c > 0))
+                .map(|s| s.id);
+        }
+    }
+
+    outln!("\n[item]");
+    match first_story {
+        Some(id) => match scraper.fetch_page(&format!("item?id={}", id)).await {
+            Ok(html) => {
+                let item = parse::parse_item(&html, &id);
+                outln!("{:<22} {}", "item", id);
+                outln!("{:<22} {}", "parsed comments", item.comments.len());
+                let counts = parse::selector_counts(&html);
+                report_counts(&counts, &ITEM_SELECTORS, "item", &mut problems);
+                if item.title.is_empty() {
+                    problems.push(format!("item {}: no title parsed", id));
+                }
+            }
+            Err(e) => outln!("item {} not fetched: {:#}", id, e),
+        },
+        None => outln!("skipped: no story with comments found"),
+    }
+
+    outln!("\n[user]");
+    match scraper.fetch_page(&format!("user?id={}", KNOWN_USER)).await {
+        Ok(html) => match parse::parse_user(&html, KNOWN_USER) {
+            Some(user) => outln!(
+                "{}: created {}, karma {}",
+                user.id,
+                user.created.map(|d| d.to_string()).unwrap_or("?".into()),
+                user.karma.map(|k| k.to_string()).unwrap_or("?".into())
+            ),
+            None => {
+                outln!("{}: no profile fields found", KNOWN_USER);
+                problems.push("user page: no profile fields parsed".to_string());
+            }
+        },
+        Err(e) => outln!("not fetched: {:#}", e),
+    }
+
+    outln!("\n[summary]");
+    if problems.is_empty() {
+        outln!("no problems found");
+    }
+    for problem in &problems {
+        outln!("- {}", problem);
+    }
+    Ok(())
+}
+
+fn check_files(problems: &mut Vec<String>) {
+    let config_path = config::config_path();
+    let config_state = match config::load_config() {
+        Ok(_) if config_path.exists() => "ok".to_string(),
+        Ok(_) => "not found, using defaults".to_string(),
+        Err(e) => {
+            problems.push(format!("config: {:#}", e));
+            format!("invalid: {:#}", e)
+        }
+    };
+    outln!("config:   {} ({})", config_path.display(), config_state);
+
+    let cache_state = match cache::inspect() {
+        Ok(files) => {
+            let broken = files.iter().filter(|f| f.problem.is_some()).count();
+            if broken > 0 {
+                problems.push(format!("cache: {} corrupt listing files", broken));
+            }
+            let (responses, _) = cache::http_cache_usage();
+            format!(
+                "{} listing files, {} corrupt, {} HTTP responses",
+                files.len(),
+                broken,
+                responses
+            )
+        }
+        Err(e) => {
+            problems.push(format!("cache: {:#}", e));
+            format!("unusable: {:#}", e)
+        }
+    };
+    outln!(
+        "cache:    {} ({})",
+        cache::cache_dir().display(),
+        cache_state
+    );
+}
+
+/// Prints every selector's match count, flagging the `required` ones that
+/// matched nothing.
+fn report_counts(
+    counts: &[(&str, usize)],
+    required: &[&str],
+    page: &str,
+    problems: &mut Vec<String>,
+) {
+    for &(css, matches) in counts {
+        let missing = matches == 0 && required.contains(&css);
+        outln!(
+            "{:<22} {}{}",
+            css,
+            matches,
+            if missing { "  <- expected matches" } else { "" }
+        );
+        if missing {
+            problems.push(format!("{}: selector `{}` matched nothing", page, css));
+        }
+    }
+}
+
+fn count(counts: &[(&str, usize)], css: &str) -> usize {
+    counts
+        .iter()
+        .find(|(name, _)| *name == css)
+        .map_or(0, |&(_, matches)| matches)
+}
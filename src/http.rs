@@ -0,0 +1,188 @@
+//! Retry policy, rate limiting and error classification for requests to HN.
+
+use crate::http_cache::CacheMode;
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Marker text of the page HN serves instead of content when it throttles us.
+const THROTTLE_MARKER: &str = "not able to serve your requests this quickly";
+const RATE_LIMIT_BURST: f64 = 4.0;
+
+/// Network behaviour of the scraper, set from the global CLI flags.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpConfig {
+    pub retries: u32,
+    pub requests_per_second: f64,
+    pub cache_mode: CacheMode,
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    /// HN answered with its "Sorry, we're not able to serve your requests this quickly" page.
+    Throttled,
+    Status {
+        code: u16,
+        retry_after: Option<Duration>,
+    },
+    Request(reqwest::Error),
+    /// The rate limiter computed a wait no `Duration` can hold.
+    RateLimit(std::time::TryFromFloatSecsError),
+}
+
+impl FetchError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            FetchError::Throttled => true,
+            FetchError::Status { code, .. } => *code == 429 || *code == 503,
+            FetchError::Request(e) => e.is_connect() || e.is_timeout(),
+            FetchError::RateLimit(_) => false,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            FetchError::Status { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Throttled => write!(f, "Hacker News is throttling requests, slow down"),
+            FetchError::Status { code, .. } => write!(f, "HTTP status {}", code),
+            FetchError::Request(e) => write!(f, "HTTP request failed: {}", e),
+            FetchError::RateLimit(e) => write!(f, "Invalid rate limit: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FetchError::Request(e) => Some(e),
+            FetchError::RateLimit(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        FetchError::Request(e)
+    }
+}
+
+/// Checks a response body for HN's throttle page.
+pub fn check_throttled(body: &str) -> Result<(), FetchError> {
+    if body.contains(THROTTLE_MARKER) {
+        Err(FetchError::Throttled)
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses a `Retry-After` header, either delta-seconds ("120") or an HTTP
+/// date ("Wed, 21 Oct 2026 07:28:00 GMT"); a date in the past means now.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+
+    /// Returns how long to wait before retrying after `error`, or `None` to give up,
+    /// which includes a `Retry-After` longer than `max_delay`.
+    pub fn backoff(&self, attempt: u32, error: &FetchError) -> Option<Duration> {
+        if attempt >= self.max_retries || !error.is_retryable() {
+            return None;
+        }
+
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        // "Equal jitter": half fixed, half random, so concurrent retries spread out.
+        let half = exponential / 2;
+        let jittered = half + half.mul_f64(fastrand::f64());
+
+        // A server asking for more than `max_delay` won't be ready any time
+        // soon, so report the throttling instead of hanging.
+        match error.retry_after() {
+            Some(wait) if wait > self.max_delay => None,
+            Some(wait) => Some(wait.max(jittered)),
+            None => Some(jittered),
+        }
+    }
+}
+
+/// Token bucket shared by every request the scraper makes.
+pub struct RateLimiter {
+    requests_per_second: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    /// A rate of zero (or less) disables limiting.
+    pub fn new(requests_per_second: f64) -> Self {
+        Self {
+            requests_per_second,
+            state: Mutex::new((RATE_LIMIT_BURST, Instant::now())),
+        }
+    }
+
+    pub async fn acquire(&self) -> Result<(), FetchError> {
+        if self.requests_per_second <= 0.0 {
+            return Ok(());
+        }
+
+        let wait = {
+            let mut state = self.state.lock().await;
+            let (tokens, last) = &mut *state;
+            let now = Instant::now();
+            *tokens = (*tokens
+                + now.duration_since(*last).as_secs_f64() * self.requests_per_second)
+                .min(RATE_LIMIT_BURST);
+            *last = now;
+            // Reserve a token even if the bucket is empty; the debt is paid by sleeping.
+            *tokens -= 1.0;
+            if *tokens < 0.0 {
+                Duration::try_from_secs_f64(-*tokens / self.requests_per_second)
+                    .map_err(FetchError::RateLimit)?
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! On-disk cache of raw HN responses (and reader mode articles), keyed by URL, with ETag/Last-Modified revalidation.
+
+use crate::cache;
+use crate::parse::BASE_URL;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const LISTING_TTL_SECONDS: u64 = 60;
+const ITEM_TTL_SECONDS: u64 = 300;
+const USER_TTL_SECONDS: u64 = 3600;
+/// Linked articles (reader mode) rarely change once published.
+const ARTICLE_TTL_SECONDS: u64 = 86400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Serve fresh entries from disk, revalidate or refetch stale ones.
+    Normal,
+    /// Ignore freshness and always ask HN (still conditionally).
+    Refresh,
+    /// Never touch the network; serve whatever is on disk.
+    Offline,
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub fetched_at: u64,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+impl CachedResponse {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now_secs().saturating_sub(self.fetched_at))
+    }
+
+    fn to_file_content(&self, url: &str) -> String {
+        format!(
+            "url {}\nfetched {}\netag {}\nlast-modified {}\n\n{}",
+            url,
+            self.fetched_at,
+            self.etag.as_deref().unwrap_or(""),
+            self.last_modified.as_deref().unwrap_or(""),
+            self.body
+        )
+    }
+
+    /// Parses an entry, or returns `None` if it is malformed or was stored for
+    /// a different URL whose name hashes the same.
+    fn from_file_content(content: &str, url: &str) -> Option<Self> {
+        let (header, body) = content.split_once("\n\n")?;
+
+        let mut stored_url = None;
+        let mut fetched_at = None;
+        let mut etag = None;
+        let mut last_modified = None;
+        for line in header.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = (!value.is_empty()).then(|| value.to_string());
+            match key {
+                "url" => stored_url = value,
+                "fetched" => fetched_at = value.and_then(|v| v.parse().ok()),
+                "etag" => etag = value,
+                "last-modified" => last_modified = value,
+                _ => {}
+            }
+        }
+
+        if stored_url.as_deref() != Some(url) {
+            return None;
+        }
+        Some(Self {
+            fetched_at: fetched_at?,
+            etag,
+            last_modified,
+            body: body.to_string(),
+        })
+    }
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// How long a response stays fresh: listings change fast, profiles rarely.
+pub fn ttl_for(url: &str) -> Duration {
+    let Some(path) = url.strip_prefix(BASE_URL) else {
+        return Duration::from_secs(ARTICLE_TTL_SECONDS);
+    };
+    let path = path.trim_start_matches('/');
+    let seconds = if path.starts_with("item") {
+        ITEM_TTL_SECONDS
+    } else if path.starts_with("user") {
+        USER_TTL_SECONDS
+    } else {
+        LISTING_TTL_SECONDS
+    };
+    Duration::from_secs(seconds)
+}
+
+pub struct HttpCache {
+    dir: PathBuf,
+    pub mode: CacheMode,
+}
+
+impl HttpCache {
+    pub fn new(mode: CacheMode) -> Self {
+        Self {
+            dir: cache::cache_dir().join("http"),
+            mode,
+        }
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.http", fnv1a(url)))
+    }
+
+    pub fn load(&self, url: &str) -> Option<CachedResponse> {
+        let content = fs::read_to_string(self.path(url)).ok()?;
+        CachedResponse::from_file_content(&content, url)
+    }
+
+    pub fn store(&self, url: &str, response: &CachedResponse) -> Result<()> {
+        fs::create_dir_all(&self.dir).context("Failed to create HTTP cache directory")?;
+        cache::write_atomic(&self.path(url), &response.to_file_content(url))
+            .context("Failed to write HTTP cache entry")
+    }
+}
+
+/// FNV-1a, used for file names because it is stable across Rust versions.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
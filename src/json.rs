@@ -0,0 +1,227 @@
+//! Minimal JSON writer for the serve mode (no Serde, same as the cache).
+
+use crate::model::{Comment, Item, PollOption, Story, StoryFlag, StoryKind, User};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+pub trait ToJson {
+    fn write_json(&self, out: &mut String);
+
+    fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+}
+
+/// Builds a JSON object field by field.
+pub struct Object {
+    buf: String,
+    empty: bool,
+}
+
+impl Object {
+    pub fn new() -> Self {
+        Self {
+            buf: String::from("{"),
+            empty: true,
+        }
+    }
+
+    pub fn field(mut self, key: &str, value: &(impl ToJson + ?Sized)) -> Self {
+        if !self.empty {
+            self.buf.push(',');
+        }
+        self.empty = false;
+        key.write_json(&mut self.buf);
+        self.buf.push(':');
+        value.write_json(&mut self.buf);
+        self
+    }
+
+    pub fn finish(mut self, out: &mut String) {
+        self.buf.push('}');
+        out.push_str(&self.buf);
+    }
+}
+
+impl ToJson for str {
+    fn write_json(&self, out: &mut String) {
+        out.push('"');
+        for c in self.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if (c as u32) < 0x20 => {
+                    let _ = write!(out, "\\u{:04x}", c as u32);
+                }
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+    }
+}
+
+impl ToJson for String {
+    fn write_json(&self, out: &mut String) {
+        self.as_str().write_json(out);
+    }
+}
+
+macro_rules! number_to_json {
+    ($($ty:ty),*) => {
+        $(impl ToJson for $ty {
+            fn write_json(&self, out: &mut String) {
+                let _ = write!(out, "{}", self);
+            }
+        })*
+    };
+}
+
+number_to_json!(usize, u64, i64);
+
+impl ToJson for bool {
+    fn write_json(&self, out: &mut String) {
+        out.push_str(if *self { "true" } else { "false" });
+    }
+}
+
+impl ToJson for NaiveDate {
+    fn write_json(&self, out: &mut String) {
+        self.format("%Y-%m-%d").to_string().write_json(out);
+    }
+}
+
+impl ToJson for DateTime<Utc> {
+    fn write_json(&self, out: &mut String) {
+        self.to_rfc3339_opts(SecondsFormat::Secs, true)
+            .write_json(out);
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn write_json(&self, out: &mut String) {
+        match self {
+            Some(value) => value.write_json(out),
+            None => out.push_str("null"),
+        }
+    }
+}
+
+/// Writes `values` as a JSON array.
+fn write_array<'a, T: ToJson + 'a>(values: impl IntoIterator<Item = &'a T>, out: &mut String) {
+    out.push('[');
+    for (idx, value) in values.into_iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        value.write_json(out);
+    }
+    out.push(']');
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn write_json(&self, out: &mut String) {
+        write_array(self, out);
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeSet<T> {
+    fn write_json(&self, out: &mut String) {
+        write_array(self, out);
+    }
+}
+
+impl ToJson for StoryKind {
+    fn write_json(&self, out: &mut String) {
+        self.name().write_json(out);
+    }
+}
+
+impl ToJson for StoryFlag {
+    fn write_json(&self, out: &mut String) {
+        self.name().write_json(out);
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn write_json(&self, out: &mut String) {
+        self.as_slice().write_json(out);
+    }
+}
+
+impl ToJson for Story {
+    fn write_json(&self, out: &mut String) {
+        Object::new()
+            .field("rank", &self.rank)
+            .field("id", &self.id)
+            .field("title", &self.title)
+            .field("url", &self.url)
+            .field("points", &self.points)
+            .field("author", &self.author)
+            .field("comments", &self.comments)
+            .field("posted", &self.posted)
+            .field("kind", &self.kind)
+            .field("flags", &self.flags)
+            .finish(out);
+    }
+}
+
+impl ToJson for Comment {
+    fn write_json(&self, out: &mut String) {
+        Object::new()
+            .field("id", &self.id)
+            .field("author", &self.author)
+            .field("posted", &self.posted)
+            .field("depth", &self.depth)
+            .field("text", &self.text)
+            .field("story_id", &self.story_id)
+            .field("story_title", &self.story_title)
+            .finish(out);
+    }
+}
+
+impl ToJson for PollOption {
+    fn write_json(&self, out: &mut String) {
+        Object::new()
+            .field("id", &self.id)
+            .field("text", &self.text)
+            .field("points", &self.points)
+            .finish(out);
+    }
+}
+
+impl ToJson for Item {
+    fn write_json(&self, out: &mut String) {
+        Object::new()
+            .field("id", &self.id)
+            .field("title", &self.title)
+            .field("url", &self.url)
+            .field("points", &self.points)
+            .field("author", &self.author)
+            .field("posted", &self.posted)
+            .field("kind", &self.kind)
+            .field("flags", &self.flags)
+            .field("text", &self.text)
+            .field("poll", &self.poll)
+            .field("comments", &self.comments)
+            .finish(out);
+    }
+}
+
+impl ToJson for User {
+    fn write_json(&self, out: &mut String) {
+        Object::new()
+            .field("id", &self.id)
+            .field("created", &self.created)
+            .field("karma", &self.karma)
+            .field("about", &self.about)
+            .field("submissions", &self.submissions)
+            .field("comments", &self.comments)
+            .finish(out);
+    }
+}
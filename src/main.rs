@@ -1,41 +1,42 @@
+mod cache;
+mod clipboard;
+mod config;
+mod csv;
+mod display;
+mod doctor;
+mod http;
+mod http_cache;
+mod json;
+mod model;
+mod pager;
+mod parse;
+mod readability;
+mod render;
+mod scraper;
+mod server;
+
 use anyhow::{Context, Result, bail};
-use clap::{Parser, Subcommand};
+use cache::{load_cached_stories, load_listing, save_listing, save_stories};
+use chrono::{Days, NaiveDate, TimeDelta, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use colored::*;
-use reqwest::Client;
-use scraper::{Html, Selector};
-use std::fs;
+use config::Config;
+use display::{
+    TimeFormat, display_article, display_comment_list, display_comment_matches,
+    display_hiring_posts, display_item, display_jobs, display_stories, display_user,
+    display_users_side_by_side, format_age,
+};
+use futures::StreamExt;
+use http::HttpConfig;
+use http_cache::CacheMode;
+use model::{Comment, HiringPost, Job, Story, StoryKind};
+use pager::outln;
+use parse::{BASE_URL, parse_hiring_post, parse_job};
+use scraper::{HnScraper, category_endpoint};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::path::PathBuf;
-use std::sync::OnceLock;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-
-const BASE_URL: &str = "https://news.ycombinator.com";
-const ITEMS_PER_PAGE: usize = 30;
-const CACHE_TTL_SECONDS: u64 = 300; // 5 minutes
-
-// Safe selector init
-macro_rules! safe_selector {
-    ($name:ident, $pattern:expr) => {
-        fn $name() -> &'static Selector {
-            static CELL: OnceLock<Selector> = OnceLock::new();
-            CELL.get_or_init(|| {
-                Selector::parse($pattern)
-                    .unwrap_or_else(|_| panic!("Invalid CSS selector: {}", $pattern))
-            })
-        }
-    };
-}
-
-safe_selector!(row_selector, "tr.athing");
-safe_selector!(subtext_selector, "tr > td.subtext");
-safe_selector!(title_selector, "span.titleline > a");
-safe_selector!(rank_selector, "span.rank");
-safe_selector!(score_selector, "span.score");
-safe_selector!(age_selector, "span.age a");
-safe_selector!(user_selector, "a.hnuser");
-safe_selector!(comment_link_selector, "a");
-safe_selector!(title_display_selector, "span.titleline");
-safe_selector!(text_selector, "div.toptext");
-safe_selector!(comment_selector, "tr.athing.comtr");
 
 #[derive(Parser)]
 #[command(name = "hn")]
@@ -43,6 +44,57 @@ safe_selector!(comment_selector, "tr.athing.comtr");
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Retries for throttled (429/503) or failed requests
+    #[arg(long, global = true, default_value_t = 3)]
+    retries: u32,
+    /// Maximum requests per second sent to HN (0 disables the limit)
+    #[arg(long, global = true, default_value_t = 2.0, value_parser = parse_rate_limit)]
+    rate_limit: f64,
+    /// Serve everything from the local cache, never touching the network
+    #[arg(long, global = true, conflicts_with = "refresh")]
+    offline: bool,
+    /// Ignore cached responses and ask HN again
+    #[arg(long, global = true)]
+    refresh: bool,
+    /// Print long output directly instead of through a pager
+    #[arg(long, global = true)]
+    no_pager: bool,
+    /// How to show story and comment times
+    #[arg(long, global = true, value_enum, default_value_t = TimeFormat::Relative)]
+    time: TimeFormat,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    #[arg(short, long, default_value_t = 1)]
+    page: usize,
+    /// Order stories by rank, age (newest first), points or comments
+    #[arg(long, value_enum, default_value_t = SortKey::Rank)]
+    sort: SortKey,
+    /// Hide stories older than this: a number with a unit (m, h, d or w), e.g. 90m, 6h, 2d, 1w
+    #[arg(long, value_parser = parse_max_age)]
+    max_age: Option<TimeDelta>,
+    /// Only show stories of this type
+    #[arg(long = "type", value_enum)]
+    kind: Option<StoryKind>,
+    /// Hide dead stories
+    #[arg(long)]
+    hide_dead: bool,
+}
+
+/// Options of listings that can only be paged, such as comment listings.
+#[derive(Args)]
+struct PageArgs {
+    #[arg(short, long, default_value_t = 1)]
+    page: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SortKey {
+    Rank,
+    Age,
+    Points,
+    Comments,
 }
 
 #[derive(Subcommand)]
@@ -50,51 +102,199 @@ enum Commands {
     /// List top stories (default)
     #[command(alias = "t")]
     Top {
-        #[arg(short, long, default_value_t = 1)]
-        page: usize,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// List new stories
     #[command(alias = "n")]
     New {
-        #[arg(short, long, default_value_t = 1)]
-        page: usize,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// List best stories
     #[command(alias = "b")]
     Best {
-        #[arg(short, long, default_value_t = 1)]
-        page: usize,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// List Ask HN stories
     #[command(alias = "a")]
     Ask {
-        #[arg(short, long, default_value_t = 1)]
-        page: usize,
+        #[command(flatten)]
+        list: ListArgs,
+        /// Only show polls
+        #[arg(long)]
+        polls: bool,
     },
     /// List Show HN stories
     #[command(alias = "s")]
     Show {
-        #[arg(short, long, default_value_t = 1)]
-        page: usize,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// List Job stories
     #[command(alias = "j")]
     Job {
-        #[arg(short, long, default_value_t = 1)]
-        page: usize,
+        #[command(flatten)]
+        list: ListArgs,
+        #[command(flatten)]
+        filter: JobFilter,
+        /// Save the matching jobs to a CSV file
+        #[arg(long, value_name = "FILE")]
+        csv: Option<PathBuf>,
+    },
+    /// List a past front page
+    Front {
+        /// Day to show (YYYY-MM-DD in UTC, default: yesterday)
+        #[arg(short, long)]
+        day: Option<NaiveDate>,
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// Show the front page of a past day, or a digest of several days
+    Past {
+        /// Day to show (YYYY-MM-DD in UTC, default: yesterday)
+        #[arg(conflicts_with_all = ["days_ago", "range"])]
+        date: Option<NaiveDate>,
+        /// Show the front page from this many days ago
+        #[arg(long, conflicts_with = "range")]
+        days_ago: Option<u64>,
+        /// Merge the top stories of an inclusive range of up to 31 days, e.g. 2024-03-01..2024-03-07
+        #[arg(long, value_parser = parse_day_range, conflicts_with = "page")]
+        range: Option<(NaiveDate, NaiveDate)>,
+        /// Stories to keep per day with --range
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// List stories with the most active discussions
+    Active {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// List stories submitted by new accounts
+    Noob {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// List new Show HN stories
+    Shownew {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// List new Ask HN stories
+    Asknew {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// List the second-chance pool
+    Pool {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// List YC company launches
+    Launches {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// List the classic front page (votes by long-time users only)
+    Classic {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// List the newest comments
+    Newcomments {
+        #[command(flatten)]
+        pages: PageArgs,
+    },
+    /// List the highest voted recent comments
+    Bestcomments {
+        #[command(flatten)]
+        pages: PageArgs,
+    },
+    /// List stories from a site
+    From {
+        /// Domain, e.g. github.com
+        site: String,
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// Alias of `user <username> --submissions`
+    #[command(hide = true)]
+    Submitted {
+        username: String,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// Show story details and comments by rank from cache
     #[command(alias = "d")]
     Details {
         #[arg(help = "Story rank from the list or item ID")]
         id_or_rank: String,
+        #[command(flatten)]
+        filter: CommentFilter,
+        /// Highlight comments by the story's submitter
+        #[arg(long)]
+        op: bool,
+        /// Collapse replies nested deeper than this (0 = top-level only)
+        #[arg(long)]
+        max_depth: Option<usize>,
     },
-    /// Open story in browser
+    /// Read a story's linked article in the terminal
+    #[command(alias = "r")]
+    Read {
+        #[arg(help = "Story rank from the list or item ID")]
+        id_or_rank: String,
+    },
+    /// Open stories in the browser
     #[command(alias = "o")]
-    Open { index: usize },
-    /// Show user details
+    Open {
+        /// Ranks from the last listing, ranges like 1-5, or item IDs
+        #[arg(required = true)]
+        targets: Vec<String>,
+        /// Open the HN discussion instead of the link
+        #[arg(long, conflicts_with = "both")]
+        comments: bool,
+        /// Open both the link and the HN discussion
+        #[arg(long)]
+        both: bool,
+        /// Print the URLs instead of opening them
+        #[arg(long, conflicts_with = "copy")]
+        print: bool,
+        /// Copy the URLs to the clipboard instead of opening them
+        #[arg(long)]
+        copy: bool,
+    },
+    /// Show user details, submissions, comments or favorites
     #[command(alias = "u")]
-    User { username: String },
+    User {
+        /// One username, or several to compare them side by side
+        #[arg(required = true)]
+        usernames: Vec<String>,
+        /// List the stories the user submitted
+        #[arg(long, conflicts_with_all = ["comments", "favorites"])]
+        submissions: bool,
+        /// List the user's comments
+        #[arg(long, conflicts_with = "favorites")]
+        comments: bool,
+        /// List the user's favorite stories
+        #[arg(long)]
+        favorites: bool,
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// Search the monthly "Who is hiring?" threads
+    Hiring {
+        /// Which monthly thread to search
+        #[arg(short, long, value_enum, default_value_t = HiringThread::Hiring)]
+        thread: HiringThread,
+        /// Month of the thread (YYYY-MM, default: the latest)
+        #[arg(long, value_parser = parse_month)]
+        month: Option<NaiveDate>,
+        #[command(flatten)]
+        filter: HiringFilter,
+    },
     /// Fetch multiple pages at once
     #[command(alias = "m")]
     Multi {
@@ -102,683 +302,964 @@ enum Commands {
         category: String,
         #[arg(short, long, default_value = "3")]
         num_pages: usize,
+        /// Maximum number of pages fetched at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+    /// Serve stories, items and users as JSON over local HTTP
+    Serve {
+        #[arg(short, long, default_value = "127.0.0.1:8080")]
+        bind: String,
+    },
+    /// Check HN's page layout, the network, cache and config for problems
+    Doctor,
+    /// Inspect or clear the local cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
     },
 }
 
-#[derive(Debug, Clone)]
-struct Story {
-    rank: usize,
-    id: String,
-    title: String,
-    url: Option<String>,
-    points: Option<usize>,
-    author: Option<String>,
-    comments: Option<usize>,
-    age: Option<String>,
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show the cached listings and HTTP responses
+    Info,
+    /// Remove all cached stories and HTTP responses
+    Clear,
+    /// Check every cached listing for malformed lines
+    Verify,
+    /// Print the cache directory
+    Path,
 }
 
-impl Story {
-    fn to_cache_line(&self) -> String {
-        format!(
-            "{}|{}|{}|{}|{}|{}|{}",
-            self.rank,
-            self.id,
-            self.title.replace('|', "∣"),
-            self.url.as_ref().map(|s| s.as_str()).unwrap_or(""),
-            self.points.map(|p| p.to_string()).unwrap_or_default(),
-            self.author.as_ref().map(|s| s.as_str()).unwrap_or(""),
-            self.comments.map(|c| c.to_string()).unwrap_or_default()
-        )
-    }
-
-    fn from_cache_line(line: &str) -> Option<Self> {
-        let parts: Vec<&str> = line.split('|').collect();
-        if parts.len() != 7 {
-            return None;
-        }
-
-        Some(Story {
-            rank: parts[0].parse().ok()?,
-            id: parts[1].to_string(),
-            title: parts[2].replace('∣', "|"),
-            url: if parts[3].is_empty() {
-                None
-            } else {
-                Some(parts[3].to_string())
-            },
-            points: parts[4].parse().ok(),
-            author: if parts[5].is_empty() {
-                None
-            } else {
-                Some(parts[5].to_string())
-            },
-            comments: parts[6].parse().ok(),
-            age: None,
-        })
-    }
+/// Fetches one listing page, caches it for `open`/`details` and prints it.
+async fn list_stories(
+    scraper: &HnScraper,
+    endpoint: &str,
+    list: &ListArgs,
+    what: &str,
+) -> Result<()> {
+    list_stories_where(scraper, endpoint, list, what, |_| true).await
 }
 
-struct HnScraper {
-    client: Client,
+/// Like `list_stories`, but only displays the stories matching `keep`.
+async fn list_stories_where(
+    scraper: &HnScraper,
+    endpoint: &str,
+    list: &ListArgs,
+    what: &str,
+    keep: impl Fn(&Story) -> bool,
+) -> Result<()> {
+    let stories = scraper
+        .fetch_stories(endpoint, list.page)
+        .await
+        .with_context(|| format!("Failed to fetch {}", what))?;
+    save_listing(
+        endpoint,
+        list.page,
+        &stories,
+        scraper.offline_age().unwrap_or_default(),
+    )?;
+
+    let mut shown = arrange(stories, list);
+    shown.retain(|story| keep(story));
+    display_stories(&shown);
+    Ok(())
 }
 
-impl HnScraper {
-    fn new() -> Result<Self> {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/143.0.0.0 Safari/537.36")
-            .pool_max_idle_per_host(10)
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
-            .build()
-            .context("Failed to build HTTP client")?;
+#[derive(Args)]
+struct CommentFilter {
+    /// Only show comments by this user
+    #[arg(long)]
+    author: Option<String>,
+    /// Only show comments containing this text
+    #[arg(long)]
+    grep: Option<String>,
+    /// Only show top-level comments
+    #[arg(long, conflicts_with = "min_depth")]
+    top_level_only: bool,
+    /// Only show replies nested at least this deep (0 = top-level)
+    #[arg(long)]
+    min_depth: Option<usize>,
+}
 
-        Ok(Self { client })
+impl CommentFilter {
+    fn is_active(&self) -> bool {
+        self.author.is_some()
+            || self.grep.is_some()
+            || self.top_level_only
+            || self.min_depth.is_some()
     }
 
-    async fn fetch_stories(&self, endpoint: &str, page: usize) -> Result<Vec<Story>> {
-        let url = if page > 1 {
-            format!("{}/{}?p={}", BASE_URL, endpoint, page)
-        } else {
-            format!("{}/{}", BASE_URL, endpoint)
-        };
-
-        let html = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to send HTTP request")?
-            .text()
-            .await
-            .context("Failed to read response text")?;
+    fn matches(&self, comment: &Comment) -> bool {
+        self.author.as_ref().is_none_or(|author| {
+            comment
+                .author
+                .as_deref()
+                .is_some_and(|a| a.eq_ignore_ascii_case(author))
+        }) && self
+            .grep
+            .as_ref()
+            .is_none_or(|grep| comment.text.to_lowercase().contains(&grep.to_lowercase()))
+            && (!self.top_level_only || comment.depth == 0)
+            && self.min_depth.is_none_or(|min| comment.depth >= min)
+    }
+}
 
-        let document = Html::parse_document(&html);
-        let mut stories = Vec::with_capacity(ITEMS_PER_PAGE);
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum HiringThread {
+    /// "Ask HN: Who is hiring?"
+    Hiring,
+    /// "Ask HN: Who wants to be hired?"
+    Hired,
+    /// "Ask HN: Freelancer? Seeking freelancer?"
+    Freelancer,
+}
 
-        let story_rows: Vec<_> = document.select(row_selector()).collect();
-        let subtext_rows: Vec<_> = document.select(subtext_selector()).collect();
+impl HiringThread {
+    /// Lowercase text identifying the thread's title.
+    fn title(self) -> &'static str {
+        match self {
+            HiringThread::Hiring => "who is hiring?",
+            HiringThread::Hired => "who wants to be hired?",
+            HiringThread::Freelancer => "freelancer?",
+        }
+    }
+}
 
-        for (idx, row) in story_rows.iter().enumerate() {
-            let id = row.value().attr("id").unwrap_or("unknown").to_string();
+#[derive(Args)]
+struct HiringFilter {
+    /// Only show posts containing this text
+    #[arg(long)]
+    grep: Option<String>,
+    /// Only show posts offering remote work
+    #[arg(long)]
+    remote: bool,
+    /// Only show posts offering onsite or hybrid work
+    #[arg(long)]
+    onsite: bool,
+    /// Only show posts whose location contains this text
+    #[arg(long)]
+    location: Option<String>,
+    /// Only show posts mentioning visa sponsorship
+    #[arg(long)]
+    visa: bool,
+}
 
-            let rank = row
-                .select(rank_selector())
-                .next()
-                .and_then(|r| r.inner_html().trim_end_matches('.').parse().ok())
-                .unwrap_or((page - 1) * ITEMS_PER_PAGE + idx + 1);
+impl HiringFilter {
+    fn matches(&self, post: &HiringPost) -> bool {
+        let contains =
+            |text: &str, needle: &str| text.to_lowercase().contains(&needle.to_lowercase());
+        // Without a parsed location, look for it anywhere in the header line.
+        let location = post
+            .location
+            .as_deref()
+            .unwrap_or_else(|| post.comment.text.lines().next().unwrap_or_default());
+
+        (!self.remote || post.remote)
+            && (!self.onsite || post.onsite)
+            && (!self.visa || post.visa)
+            && self.location.as_ref().is_none_or(|l| contains(location, l))
+            && self
+                .grep
+                .as_ref()
+                .is_none_or(|g| contains(&post.comment.text, g))
+    }
+}
 
-            let title_elem = row.select(title_selector()).next();
-            let title = title_elem.map(|e| e.inner_html()).unwrap_or_default();
+#[derive(Args)]
+struct JobFilter {
+    /// Only show remote jobs
+    #[arg(long)]
+    remote: bool,
+    /// Only show jobs whose location contains this text
+    #[arg(long)]
+    location: Option<String>,
+    /// Only show companies from a YC batch, e.g. "S21" or "W"
+    #[arg(long)]
+    batch: Option<String>,
+    /// Only show jobs whose role contains this text
+    #[arg(long)]
+    role: Option<String>,
+}
 
-            if title.is_empty() {
-                continue;
+impl JobFilter {
+    fn matches(&self, job: &Job) -> bool {
+        let contains = |field: Option<&str>, needle: &Option<String>| match needle {
+            Some(needle) => {
+                field.is_some_and(|f| f.to_lowercase().contains(&needle.to_lowercase()))
             }
+            None => true,
+        };
+        let batch = self.batch.as_ref().map(|b| {
+            let b = b.trim().to_uppercase();
+            b.strip_prefix("YC").unwrap_or(&b).trim().to_string()
+        });
+
+        (!self.remote || job.remote)
+            && contains(job.location.as_deref(), &self.location)
+            && contains(job.batch.as_deref(), &batch)
+            && contains(job.role.as_deref().or(Some(&job.story.title)), &self.role)
+    }
+}
 
-            let url = title_elem.and_then(|e| e.value().attr("href")).map(|s| {
-                if s.starts_with("http") {
-                    s.to_string()
-                } else {
-                    format!("{}/{}", BASE_URL, s.trim_start_matches('/'))
-                }
-            });
-
-            let mut points = None;
-            let mut author = None;
-            let mut comments = None;
-            let mut age = None;
-
-            if let Some(subtext) = subtext_rows.get(idx) {
-                if let Some(score) = subtext.select(score_selector()).next() {
-                    points = score
-                        .inner_html()
-                        .split_whitespace()
-                        .next()
-                        .and_then(|s| s.parse().ok());
-                }
-
-                if let Some(user) = subtext.select(user_selector()).next() {
-                    author = Some(user.inner_html());
-                }
+/// Numbers above this can't be ranks from a listing, so they are item IDs.
+const MAX_RANK: usize = 9999;
 
-                if let Some(age_elem) = subtext.select(age_selector()).next() {
-                    age = Some(age_elem.inner_html());
-                }
+/// Which cached listing a rank refers to: `None` for the last listing shown,
+/// or a listing's endpoint and page.
+type ListingRef = Option<(String, usize)>;
 
-                for link in subtext.select(comment_link_selector()) {
-                    let text = link.inner_html();
-                    if text.contains("comment") || text.contains("discuss") {
-                        comments = text
-                            .split_whitespace()
-                            .next()
-                            .and_then(|s| s.replace("&nbsp;", "").parse().ok());
-                        break;
-                    }
-                }
-            }
+/// Splits a target like `top:3`, `new/2:45` or `ask:1-5` into its listing
+/// and the rank part. Targets without a listing (`3`) refer to the last one.
+fn split_listing(target: &str) -> Result<(ListingRef, &str)> {
+    let Some((listing, ranks)) = target.rsplit_once(':') else {
+        return Ok((None, target));
+    };
+    let (name, page) = match listing.rsplit_once('/') {
+        Some((name, page)) => match page.parse::<usize>() {
+            Ok(page) if page > 0 => (name, page),
+            _ => bail!(
+                "Invalid page in '{}', expected something like new/2:5",
+                target
+            ),
+        },
+        None => (listing, 1),
+    };
+    if name.is_empty() {
+        bail!(
+            "Missing listing in '{}', expected something like top:3",
+            target
+        );
+    }
+    let endpoint = category_endpoint(name).unwrap_or(name);
+    Ok((Some((endpoint.to_string(), page)), ranks))
+}
 
-            stories.push(Story {
-                rank,
-                id,
-                title,
-                url,
-                points,
-                author,
-                comments,
-                age,
-            });
+/// Turns a rank (optionally prefixed with its listing) or an item ID into an
+/// item ID.
+fn resolve_item_id(target: String, offline: bool) -> Result<String> {
+    let (listing, rank) = split_listing(&target)?;
+    match rank.parse::<usize>() {
+        Ok(rank) if rank <= MAX_RANK || listing.is_some() => {
+            Ok(find_rank(&cached_listing(&listing, offline)?, rank)?.id)
         }
+        _ if listing.is_some() => bail!("Invalid rank in '{}'", target),
+        _ => Ok(target),
+    }
+}
 
-        if stories.is_empty() {
-            bail!(
-                "No stories found on page {}. The page structure may have changed.",
-                page
-            );
-        }
+fn cached_listing(listing: &ListingRef, offline: bool) -> Result<Vec<Story>> {
+    match listing {
+        None => last_listing(offline),
+        Some((endpoint, page)) => load_listing(endpoint, *page, offline).with_context(|| {
+            format!(
+                "No cached stories for '{}' page {}. Please list it first.",
+                endpoint, page
+            )
+        }),
+    }
+}
 
-        Ok(stories)
+fn last_listing(offline: bool) -> Result<Vec<Story>> {
+    load_cached_stories(offline)
+        .context("No cached stories. Please run a list command (top, new, etc.) first.")
+}
+
+fn find_rank(stories: &[Story], rank: usize) -> Result<Story> {
+    match stories.iter().find(|s| s.rank == rank) {
+        Some(story) => Ok(story.clone()),
+        None => bail!(
+            "Story with rank {} not found in cache. Run a list command first.",
+            rank
+        ),
     }
+}
 
-    async fn fetch_item(&self, id: &str) -> Result<()> {
-        let url = format!("{}/item?id={}", BASE_URL, id);
-        let html = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to fetch item")?
-            .text()
-            .await
-            .context("Failed to read item response")?;
-
-        let document = Html::parse_document(&html);
-
-        // Get title and URL
-        if let Some(title_elem) = document.select(title_display_selector()).next() {
-            // Create one-time selector for link
-            let link_sel = Selector::parse("a")
-                .map_err(|e| anyhow::anyhow!("Failed to parse link selector: {:?}", e))?;
-
-            if let Some(link) = title_elem.select(&link_sel).next() {
-                let title = link.inner_html();
-                let story_url = link.value().attr("href").map(|u| {
-                    if u.starts_with("http") {
-                        u.to_string()
-                    } else {
-                        format!("{}/{}", BASE_URL, u.trim_start_matches('/'))
-                    }
-                });
-
-                if let Some(story_url) = story_url {
-                    println!("{}", title.bright_white().bold());
-                    println!(
-                        "{} {}\n",
-                        "Link:".bright_cyan(),
-                        ansi_link(&story_url, &story_url)
-                    );
-                } else {
-                    println!("{}\n", title.bright_white().bold());
+/// Resolves `open` targets (ranks, `a-b` rank ranges, either prefixed with a
+/// listing, and item IDs) to item IDs with their links. Links of item IDs
+/// are only fetched if `need_links`.
+async fn resolve_open_targets(
+    scraper: &HnScraper,
+    targets: &[String],
+    offline: bool,
+    need_links: bool,
+) -> Result<Vec<(String, Option<String>)>> {
+    let mut listings: HashMap<ListingRef, Vec<Story>> = HashMap::new();
+    let mut resolved = Vec::new();
+    for target in targets {
+        let (listing, spec) = split_listing(target)?;
+        let ranks = match spec.split_once('-') {
+            Some((from, to)) => {
+                let (Ok(from), Ok(to)) = (from.parse::<usize>(), to.parse::<usize>()) else {
+                    bail!("Invalid range '{}', expected something like 1-5", target);
+                };
+                if from > to || to > MAX_RANK {
+                    bail!("Invalid range '{}'", target);
                 }
+                from..=to
             }
-        }
+            None => match spec.parse::<usize>() {
+                Ok(rank) if rank <= MAX_RANK || listing.is_some() => rank..=rank,
+                Ok(_) => {
+                    let link = if need_links {
+                        scraper
+                            .fetch_item(target)
+                            .await
+                            .context("Failed to fetch item details")?
+                            .url
+                    } else {
+                        None
+                    };
+                    resolved.push((target.clone(), link));
+                    continue;
+                }
+                Err(_) => bail!("'{}' is not a rank, range or item ID", target),
+            },
+        };
 
-        // Get story text if available
-        if let Some(text_elem) = document.select(text_selector()).next() {
-            let text = text_elem.text().collect::<String>();
-            if !text.trim().is_empty() {
-                println!("{}\n", text.trim());
+        let stories = match listings.entry(listing) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let stories = cached_listing(entry.key(), offline)?;
+                entry.insert(stories)
             }
+        };
+        for rank in ranks {
+            let story = find_rank(stories, rank)?;
+            resolved.push((story.id, story.url));
         }
+    }
+    Ok(resolved)
+}
 
-        // Display comments
-        let comment_count = document.select(comment_selector()).count();
-
-        if comment_count == 0 {
-            println!("{}", "No comments yet".bright_black());
-            return Ok(());
+fn cache_command(action: CacheAction) -> Result<()> {
+    match action {
+        CacheAction::Path => outln!("{}", cache::cache_dir().display()),
+        CacheAction::Clear => {
+            cache::clear()?;
+            outln!("{} Cleared {}", "✓".green(), cache::cache_dir().display());
         }
-
-        println!(
-            "{} {}\n",
-            "Comments:".bright_cyan().bold(),
-            format!("({} total)", comment_count).bright_black()
-        );
-
-        // Create selectors for comment parsing
-        let comhead_selector = Selector::parse("span.comhead")
-            .map_err(|e| anyhow::anyhow!("Failed to parse comhead selector: {:?}", e))?;
-        let commtext_selector = Selector::parse("div.commtext")
-            .map_err(|e| anyhow::anyhow!("Failed to parse commtext selector: {:?}", e))?;
-        let ind_selector = Selector::parse("td.ind")
-            .map_err(|e| anyhow::anyhow!("Failed to parse ind selector: {:?}", e))?;
-        let author_selector = Selector::parse("a.hnuser")
-            .map_err(|e| anyhow::anyhow!("Failed to parse author selector: {:?}", e))?;
-        let age_comment_selector = Selector::parse("span.age a")
-            .map_err(|e| anyhow::anyhow!("Failed to parse age selector: {:?}", e))?;
-
-        for (idx, comment_row) in document.select(comment_selector()).enumerate() {
-            if idx >= 10 {
-                println!(
-                    "\n{}",
-                    format!("... {} more comments", comment_count - 10).bright_black()
-                );
-                break;
+        CacheAction::Info => {
+            let files = cache::inspect()?;
+            outln!(
+                "{}\n",
+                cache::cache_dir()
+                    .display()
+                    .to_string()
+                    .bright_white()
+                    .bold()
+            );
+            if files.is_empty() {
+                outln!("No cached listings.");
             }
-
-            let indent_level = comment_row
-                .select(&ind_selector)
-                .next()
-                .and_then(|td| td.value().attr("indent"))
-                .and_then(|i| i.parse::<usize>().ok())
-                .unwrap_or(0);
-
-            let indent = "  ".repeat(indent_level);
-
-            if let Some(comhead) = comment_row.select(&comhead_selector).next() {
-                let author = comhead
-                    .select(&author_selector)
-                    .next()
-                    .map(|a| a.inner_html())
-                    .unwrap_or_else(|| "[deleted]".to_string());
-
-                let age = comhead
-                    .select(&age_comment_selector)
-                    .next()
-                    .map(|a| a.inner_html())
-                    .unwrap_or_default();
-
-                println!(
-                    "{}{} {} {}",
-                    indent,
-                    "●".bright_black(),
-                    author.cyan(),
-                    age.bright_black()
+            for file in &files {
+                let saved = file
+                    .saved
+                    .and_then(|saved| saved.elapsed().ok())
+                    .map(format_age)
+                    .unwrap_or_else(|| "unknown".to_string());
+                let details = match &file.problem {
+                    Some(problem) => problem.red().to_string(),
+                    None => format!(
+                        "{} stories, saved {}, format v{}",
+                        file.stories,
+                        saved,
+                        file.version.unwrap_or_default()
+                    ),
+                };
+                outln!(
+                    "{:<24} {}",
+                    file.name.bright_white(),
+                    format!("{} ({} bytes)", details, file.bytes).bright_black()
                 );
             }
-
-            if let Some(commtext) = comment_row.select(&commtext_selector).next() {
-                let text = commtext.text().collect::<Vec<_>>().join(" ");
-                let cleaned_text = text
-                    .trim()
-                    .lines()
-                    .map(|line| line.trim())
-                    .filter(|line| !line.is_empty())
-                    .collect::<Vec<_>>()
-                    .join(" ");
-
-                let wrapped = wrap_text(&cleaned_text, 80 - (indent_level * 2 + 2));
-                for line in wrapped {
-                    println!("{}  {}", indent, line);
-                }
-            }
-
-            println!();
+            let (responses, bytes) = cache::http_cache_usage();
+            outln!(
+                "\n{:<24} {}",
+                "http".bright_white(),
+                format!("{} responses ({} bytes)", responses, bytes).bright_black()
+            );
         }
-
-        Ok(())
-    }
-
-    async fn fetch_user(&self, username: &str) -> Result<()> {
-        let url = format!("{}/user?id={}", BASE_URL, username);
-        let html = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to fetch user")?
-            .text()
-            .await
-            .context("Failed to read user response")?;
-
-        let document = Html::parse_document(&html);
-
-        println!(
-            "{} {}\n",
-            "Profile:".bright_cyan().bold(),
-            username.bright_white()
-        );
-
-        let tr_selector = Selector::parse("tr")
-            .map_err(|e| anyhow::anyhow!("Failed to parse tr selector: {:?}", e))?;
-        let td_selector = Selector::parse("td")
-            .map_err(|e| anyhow::anyhow!("Failed to parse td selector: {:?}", e))?;
-
-        let mut found_data = false;
-
-        for row in document.select(&tr_selector) {
-            let cells: Vec<_> = row.select(&td_selector).collect();
-
-            if cells.len() == 2 {
-                let field = cells[0].text().collect::<String>().trim().to_string();
-                let value_text = cells[1].text().collect::<String>().trim().to_string();
-
-                if field.ends_with(':') {
-                    let field_name = field.trim_end_matches(':');
-
-                    match field_name {
-                        "user" => {
-                            println!(
-                                "{}: {}",
-                                "Username".bright_yellow(),
-                                value_text.bright_white()
-                            );
-                            found_data = true;
-                        }
-                        "created" => {
-                            println!(
-                                "{}: {}",
-                                "Created".bright_yellow(),
-                                value_text.bright_white()
-                            );
-                            found_data = true;
-                        }
-                        "karma" => {
-                            println!("{}: {}", "Karma".bright_yellow(), value_text.bright_white());
-                            found_data = true;
-                        }
-                        "about" => {
-                            let about_html = cells[1].inner_html().trim().to_string();
-                            println!("{}: {}", "About".bright_yellow(), about_html.bright_white());
-                            found_data = true;
-                        }
-                        _ => {}
-                    }
+        CacheAction::Verify => {
+            let files = cache::inspect()?;
+            let broken: Vec<_> = files.iter().filter(|f| f.problem.is_some()).collect();
+            for file in &files {
+                match &file.problem {
+                    Some(problem) => outln!("{} {}: {}", "✗".red(), file.path.display(), problem),
+                    None => outln!(
+                        "{} {} ({} stories)",
+                        "✓".green(),
+                        file.path.display(),
+                        file.stories
+                    ),
                 }
             }
+            if !broken.is_empty() {
+                bail!(
+                    "{} of {} cache files are corrupt. Run `hn cache clear` to remove them.",
+                    broken.len(),
+                    files.len()
+                );
+            }
+            outln!("\n{} {} cache files OK", "✓".green(), files.len());
         }
+    }
+    Ok(())
+}
 
-        if !found_data {
-            bail!("User '{}' not found or has no public information", username);
-        }
-
-        println!();
-        Ok(())
+/// Applies `--max-age`, `--type`, `--hide-dead` and `--sort`. Stories keep their
+/// HN rank, so the full page is cached and `open <rank>` still matches what is shown.
+fn arrange(mut stories: Vec<Story>, list: &ListArgs) -> Vec<Story> {
+    // A cutoff before the earliest representable time filters nothing.
+    if let Some(cutoff) = list
+        .max_age
+        .and_then(|age| Utc::now().checked_sub_signed(age))
+    {
+        stories.retain(|s| s.posted.is_some_and(|posted| posted >= cutoff));
+    }
+    if let Some(kind) = list.kind {
+        stories.retain(|s| s.kind == kind);
+    }
+    if list.hide_dead {
+        stories.retain(|s| !s.is_dead());
     }
 
-    async fn fetch_multiple_pages(
-        &self,
-        endpoint: &str,
-        pages: Vec<usize>,
-    ) -> Result<Vec<Vec<Story>>> {
-        let futures = pages
-            .into_iter()
-            .map(|page| self.fetch_stories(endpoint, page));
-        let results = futures::future::join_all(futures).await;
-        results.into_iter().collect()
+    match list.sort {
+        SortKey::Rank => {}
+        SortKey::Age => stories.sort_by_key(|s| Reverse(s.posted)),
+        SortKey::Points => stories.sort_by_key(|s| Reverse(s.points)),
+        SortKey::Comments => stories.sort_by_key(|s| Reverse(s.comments)),
     }
+    stories
 }
 
-fn get_cache_path() -> PathBuf {
-    dirs::cache_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("hn-cli")
-        .join("stories.cache")
+/// Slowest allowed `--rate-limit` other than 0: one request a minute.
+const MIN_RATE_LIMIT: f64 = 1.0 / 60.0;
+
+fn parse_rate_limit(value: &str) -> Result<f64, String> {
+    let rate: f64 = value
+        .parse()
+        .map_err(|_| format!("expected requests per second, got '{}'", value))?;
+    if rate == 0.0 || (rate.is_finite() && rate >= MIN_RATE_LIMIT) {
+        Ok(rate)
+    } else {
+        Err(format!(
+            "must be 0 (no limit) or a number of at least {:.3} (one request a minute)",
+            MIN_RATE_LIMIT
+        ))
+    }
 }
 
-fn save_stories(stories: &[Story]) -> Result<()> {
-    let cache_path = get_cache_path();
-    if let Some(parent) = cache_path.parent() {
-        fs::create_dir_all(parent).context("Failed to create cache directory")?;
-    }
+fn parse_month(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d")
+        .map_err(|_| format!("expected a month like 2026-10, got '{}'", value))
+}
 
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .context("Failed to get system time")?
-        .as_secs();
-
-    let mut cache_content = format!("{}\n", timestamp);
-    cache_content.push_str(
-        &stories
-            .iter()
-            .map(|s| s.to_cache_line())
-            .collect::<Vec<_>>()
-            .join("\n"),
-    );
+fn parse_max_age(value: &str) -> Result<TimeDelta, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| {
+        format!(
+            "expected a number followed by m, h, d or w, got '{}'",
+            value
+        )
+    })?;
+    let delta = match unit {
+        "m" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "" => {
+            return Err(format!(
+                "'{}' needs a unit, e.g. {}h or {}d",
+                value, amount, amount
+            ));
+        }
+        "d" => TimeDelta::try_days(amount),
+        "w" => TimeDelta::try_weeks(amount),
+        _ => return Err(format!("unknown unit '{}', use m, h, d or w", unit)),
+    };
+    delta.ok_or_else(|| format!("'{}' is too large", value))
+}
 
-    fs::write(&cache_path, cache_content).context("Failed to write cache file")?;
+/// Lists a user's `submitted` or `favorites` page and caches it like any listing.
+async fn list_user_stories(
+    scraper: &HnScraper,
+    listing: &str,
+    username: &str,
+    list: &ListArgs,
+) -> Result<()> {
+    let stories = scraper
+        .fetch_user_stories(listing, username, list.page)
+        .await
+        .with_context(|| format!("Failed to fetch {} of {}", listing, username))?;
+    save_listing(
+        &format!("{}?id={}", listing, username),
+        list.page,
+        &stories,
+        scraper.offline_age().unwrap_or_default(),
+    )?;
+    display_stories(&arrange(stories, list));
     Ok(())
 }
 
-fn load_cached_stories() -> Result<Vec<Story>> {
-    let cache_path = get_cache_path();
-    let content = fs::read_to_string(&cache_path).context("Failed to read cache file")?;
-
-    let mut lines = content.lines();
-
-    if let Some(timestamp_str) = lines.next() {
-        if let Ok(timestamp) = timestamp_str.parse::<u64>() {
-            let current_time = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .context("Failed to get current time")?
-                .as_secs();
+fn front_endpoint(day: NaiveDate) -> String {
+    format!("front?day={}", day.format("%Y-%m-%d"))
+}
 
-            if current_time - timestamp > CACHE_TTL_SECONDS {
-                bail!("Cache expired");
-            }
-        }
+/// Most days `--range` may span, so a typo can't queue years of fetches.
+const MAX_RANGE_DAYS: i64 = 31;
+
+fn parse_day_range(range: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let (from, to) = range
+        .split_once("..")
+        .ok_or_else(|| "expected FROM..TO, e.g. 2024-03-01..2024-03-07".to_string())?;
+    let from: NaiveDate = from
+        .parse()
+        .map_err(|e| format!("invalid start day: {}", e))?;
+    let to: NaiveDate = to.parse().map_err(|e| format!("invalid end day: {}", e))?;
+    if from > to {
+        return Err(format!("{} is after {}", from, to));
     }
-
-    let stories: Vec<Story> = lines.filter_map(Story::from_cache_line).collect();
-
-    if stories.is_empty() {
-        bail!("No stories in cache");
+    let span = (to - from).num_days() + 1;
+    if span > MAX_RANGE_DAYS {
+        return Err(format!(
+            "{}..{} spans {} days, at most {} are allowed",
+            from, to, span, MAX_RANGE_DAYS
+        ));
     }
-
-    Ok(stories)
+    Ok((from, to))
 }
 
-fn display_stories(stories: &[Story]) {
-    for story in stories {
-        println!(
-            "{}. {} {}",
-            story.rank.to_string().bright_black(),
-            story.title.bright_white().bold(),
-            story
-                .url
-                .as_ref()
-                .map(|u| format!("({})", extract_domain(u))
-                    .bright_black()
-                    .to_string())
-                .unwrap_or_default()
+/// Merges the top `top` stories of each day's front page into one report,
+/// after applying the list filters and sort to each day. Ranks are renumbered
+/// across the report so `open`/`details` work on it.
+async fn past_digest(
+    scraper: &HnScraper,
+    from: NaiveDate,
+    to: NaiveDate,
+    top: usize,
+    list: &ListArgs,
+) -> Result<()> {
+    let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+    let mut results = futures::stream::iter(days.iter().copied())
+        .map(|day| async move { (day, scraper.fetch_stories(&front_endpoint(day), 1).await) })
+        .buffered(4);
+
+    let mut report: Vec<Story> = Vec::new();
+    while let Some((day, result)) = results.next().await {
+        outln!(
+            "{}\n",
+            format!("── {} ──", day.format("%A, %B %-d, %Y"))
+                .bright_cyan()
+                .bold()
         );
-
-        let mut meta = Vec::new();
-        if let Some(points) = story.points {
-            meta.push(format!("{} points", points).yellow().to_string());
-        }
-        if let Some(author) = &story.author {
-            meta.push(format!("by {}", author).cyan().to_string());
-        }
-        if let Some(age) = &story.age {
-            meta.push(age.bright_black().to_string());
-        }
-        if let Some(comments) = story.comments {
-            meta.push(format!("{} comments", comments).green().to_string());
-        }
-
-        if !meta.is_empty() {
-            println!("   {}", meta.join(" | "));
-        }
-        println!();
-    }
-}
-
-fn extract_domain(url: &str) -> &str {
-    url.split("://")
-        .nth(1)
-        .and_then(|s| s.split('/').next())
-        .unwrap_or(url)
-}
-
-fn wrap_text(text: &str, width: usize) -> Vec<String> {
-    let mut lines = Vec::new();
-    let mut current_line = String::new();
-
-    for word in text.split_whitespace() {
-        if current_line.len() + word.len() + 1 > width {
-            if !current_line.is_empty() {
-                lines.push(current_line.clone());
-                current_line.clear();
+        match result {
+            Ok(stories) => {
+                let mut stories: Vec<Story> =
+                    arrange(stories, list).into_iter().take(top).collect();
+                for story in &mut stories {
+                    story.rank = report.len() + 1;
+                    report.push(story.clone());
+                }
+                display_stories(&stories);
             }
+            Err(e) => eprintln!("{} {}: {:#}\n", "Warning:".yellow().bold(), day, e),
         }
-
-        if !current_line.is_empty() {
-            current_line.push(' ');
-        }
-        current_line.push_str(word);
     }
 
-    if !current_line.is_empty() {
-        lines.push(current_line);
+    if report.is_empty() {
+        bail!(
+            "No front pages could be fetched between {} and {}",
+            from,
+            to
+        );
     }
-
-    lines
-}
-
-fn ansi_link(url: &str, text: &str) -> String {
-    format!(
-        "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
-        url,
-        text.cyan().underline()
-    )
+    save_listing(
+        "past",
+        1,
+        &report,
+        scraper.offline_age().unwrap_or_default(),
+    )?;
+
+    outln!(
+        "{} {} stories from {} days",
+        "✓".green(),
+        report.len().to_string().bright_white().bold(),
+        days.len().to_string().bright_white().bold()
+    );
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
-    let scraper = HnScraper::new().context("Failed to initialize scraper")?;
-
-    match cli.command.unwrap_or(Commands::Top { page: 1 }) {
-        Commands::Top { page } => {
-            let stories = scraper
-                .fetch_stories("news", page)
-                .await
-                .context("Failed to fetch top stories")?;
-            save_stories(&stories)?;
-            display_stories(&stories);
+    display::set_time_format(cli.time);
+    // The doctor checks the live pages, not what we cached from them.
+    let cache_mode = if cli.offline {
+        CacheMode::Offline
+    } else if cli.refresh || matches!(cli.command, Some(Commands::Doctor)) {
+        CacheMode::Refresh
+    } else {
+        CacheMode::Normal
+    };
+    let scraper = HnScraper::new(HttpConfig {
+        retries: cli.retries,
+        requests_per_second: cli.rate_limit,
+        cache_mode,
+    })
+    .context("Failed to initialize scraper")?;
+
+    // A broken config file shouldn't lock the user out, least of all from
+    // `hn doctor`, which reports it.
+    let config = config::load_config().unwrap_or_else(|e| {
+        eprintln!(
+            "{} {:#}, using the default settings\n",
+            "Warning:".yellow().bold(),
+            e
+        );
+        Config::default()
+    });
+    let command = cli.command.unwrap_or(Commands::Top {
+        list: ListArgs {
+            page: 1,
+            sort: SortKey::Rank,
+            max_age: None,
+            kind: None,
+            hide_dead: false,
+        },
+    });
+    let paging = config.pager && !cli.no_pager && !matches!(command, Commands::Serve { .. });
+    let _pager = pager::start(paging, config.pager_command);
+
+    match command {
+        Commands::Top { list } => list_stories(&scraper, "news", &list, "top stories").await?,
+        Commands::New { list } => list_stories(&scraper, "newest", &list, "new stories").await?,
+        Commands::Best { list } => list_stories(&scraper, "best", &list, "best stories").await?,
+        Commands::Ask { list, polls } => {
+            let keep = |story: &Story| !polls || story.kind == StoryKind::Poll;
+            list_stories_where(&scraper, "ask", &list, "Ask HN stories", keep).await?
         }
-        Commands::New { page } => {
+        Commands::Show { list } => list_stories(&scraper, "show", &list, "Show HN stories").await?,
+        Commands::Job { list, filter, csv } => {
             let stories = scraper
-                .fetch_stories("newest", page)
+                .fetch_stories("jobs", list.page)
                 .await
-                .context("Failed to fetch new stories")?;
-            save_stories(&stories)?;
-            display_stories(&stories);
+                .context("Failed to fetch Job stories")?;
+            save_listing(
+                "jobs",
+                list.page,
+                &stories,
+                scraper.offline_age().unwrap_or_default(),
+            )?;
+
+            let jobs: Vec<Job> = arrange(stories, &list)
+                .into_iter()
+                .map(parse_job)
+                .filter(|job| filter.matches(job))
+                .collect();
+            display_jobs(&jobs);
+
+            if let Some(path) = csv {
+                csv::write_jobs(&path, &jobs)?;
+                outln!(
+                    "{} {} jobs to {}",
+                    "Saved".green(),
+                    jobs.len(),
+                    path.display()
+                );
+            }
         }
-        Commands::Best { page } => {
-            let stories = scraper
-                .fetch_stories("best", page)
-                .await
-                .context("Failed to fetch best stories")?;
-            save_stories(&stories)?;
-            display_stories(&stories);
+        Commands::Front { day, list } => {
+            let endpoint = match day {
+                Some(day) => front_endpoint(day),
+                None => "front".to_string(),
+            };
+            list_stories(&scraper, &endpoint, &list, "front page").await?;
         }
-        Commands::Ask { page } => {
-            let stories = scraper
-                .fetch_stories("ask", page)
+        Commands::Past {
+            date,
+            days_ago,
+            range,
+            top,
+            list,
+        } => {
+            if let Some((from, to)) = range {
+                past_digest(&scraper, from, to, top, &list).await?;
+            } else {
+                // HN's front?day= pages are keyed by UTC date.
+                let today = Utc::now().date_naive();
+                let day = match (date, days_ago) {
+                    (Some(date), _) => date,
+                    (None, days) => {
+                        let days = days.unwrap_or(1);
+                        today
+                            .checked_sub_days(Days::new(days))
+                            .with_context(|| format!("{} days ago is out of range", days))?
+                    }
+                };
+                let what = format!("front page for {}", day);
+                list_stories(&scraper, &front_endpoint(day), &list, &what).await?;
+            }
+        }
+        Commands::Active { list } => {
+            list_stories(&scraper, "active", &list, "active stories").await?
+        }
+        Commands::Noob { list } => {
+            list_stories(&scraper, "noobstories", &list, "noob stories").await?
+        }
+        Commands::Shownew { list } => {
+            list_stories(&scraper, "shownew", &list, "new Show HN stories").await?
+        }
+        Commands::Asknew { list } => {
+            list_stories(&scraper, "asknew", &list, "new Ask HN stories").await?
+        }
+        Commands::Pool { list } => list_stories(&scraper, "pool", &list, "pool stories").await?,
+        Commands::Launches { list } => {
+            list_stories(&scraper, "launches", &list, "launches").await?
+        }
+        Commands::Classic { list } => {
+            list_stories(&scraper, "classic", &list, "classic stories").await?
+        }
+        Commands::Newcomments { pages } => {
+            let comments = scraper
+                .fetch_comments("newcomments", pages.page)
                 .await
-                .context("Failed to fetch Ask HN stories")?;
-            save_stories(&stories)?;
-            display_stories(&stories);
+                .context("Failed to fetch new comments")?;
+            display_comment_list(&comments);
         }
-        Commands::Show { page } => {
-            let stories = scraper
-                .fetch_stories("show", page)
+        Commands::Bestcomments { pages } => {
+            let comments = scraper
+                .fetch_comments("bestcomments", pages.page)
                 .await
-                .context("Failed to fetch Show HN stories")?;
-            save_stories(&stories)?;
-            display_stories(&stories);
+                .context("Failed to fetch best comments")?;
+            display_comment_list(&comments);
         }
-        Commands::Job { page } => {
-            let stories = scraper
-                .fetch_stories("jobs", page)
+        Commands::From { site, list } => {
+            let endpoint = format!("from?site={}", site);
+            list_stories(&scraper, &endpoint, &list, "site stories").await?;
+        }
+        Commands::Submitted { username, list } => {
+            list_user_stories(&scraper, "submitted", &username, &list).await?;
+        }
+        Commands::Details {
+            id_or_rank,
+            filter,
+            op,
+            max_depth,
+        } => {
+            let id = resolve_item_id(id_or_rank, cli.offline)?;
+
+            if filter.is_active() {
+                // Searching only makes sense over the whole thread.
+                let item = scraper
+                    .fetch_full_item(&id)
+                    .await
+                    .context("Failed to fetch item details")?;
+                let matches: Vec<usize> = (0..item.comments.len())
+                    .filter(|&idx| filter.matches(&item.comments[idx]))
+                    .collect();
+                display_comment_matches(&item, &matches, filter.grep.as_deref(), op);
+            } else {
+                let item = scraper
+                    .fetch_item(&id)
+                    .await
+                    .context("Failed to fetch item details")?;
+                display_item(&item, op, max_depth);
+            }
+        }
+        Commands::Read { id_or_rank } => {
+            let id = resolve_item_id(id_or_rank, cli.offline)?;
+            let item = scraper
+                .fetch_item(&id)
                 .await
-                .context("Failed to fetch Job stories")?;
-            save_stories(&stories)?;
-            display_stories(&stories);
+                .context("Failed to fetch item details")?;
+            let Some(url) = item.url.as_deref().filter(|url| !url.starts_with(BASE_URL)) else {
+                bail!(
+                    "Item {} has no linked article. Use `hn details {}` instead.",
+                    id,
+                    id
+                );
+            };
+            let article = scraper.fetch_article(url).await?;
+            display_article(&item.title, &article);
         }
-        Commands::Details { id_or_rank } => {
-            if let Ok(rank) = id_or_rank.parse::<usize>() {
-                match load_cached_stories() {
-                    Ok(stories) => {
-                        if let Some(story) = stories.iter().find(|s| s.rank == rank) {
-                            scraper
-                                .fetch_item(&story.id)
-                                .await
-                                .context("Failed to fetch item details")?;
-                        } else {
-                            bail!(
-                                "Story with rank {} not found in cache. Run a list command first.",
-                                rank
-                            );
+        Commands::Open {
+            targets,
+            comments,
+            both,
+            print,
+            copy,
+        } => {
+            let mut urls = Vec::new();
+            for (id, link) in
+                resolve_open_targets(&scraper, &targets, cli.offline, !comments).await?
+            {
+                let discussion = format!("{}/item?id={}", BASE_URL, id);
+                // Stories without an external link only have the discussion.
+                let link = link.filter(|url| !url.starts_with(BASE_URL));
+                match link {
+                    Some(link) if !comments => {
+                        urls.push(link);
+                        if both {
+                            urls.push(discussion);
                         }
                     }
-                    Err(_) => {
-                        bail!(
-                            "No cached stories. Please run a list command (top, new, etc.) first."
-                        );
-                    }
+                    _ => urls.push(discussion),
+                }
+            }
+
+            if print {
+                for url in &urls {
+                    outln!("{}", url);
                 }
+            } else if copy {
+                let method = clipboard::copy(&urls.join("\n"))?;
+                outln!(
+                    "{} {} {} ({})",
+                    "Copied".green(),
+                    urls.len(),
+                    if urls.len() == 1 { "URL" } else { "URLs" },
+                    method
+                );
             } else {
-                scraper
-                    .fetch_item(&id_or_rank)
-                    .await
-                    .context("Failed to fetch item details")?;
+                for url in &urls {
+                    open::that(url)
+                        .with_context(|| format!("Failed to open {} in browser", url))?;
+                    outln!("{} {}", "Opened:".green(), url);
+                }
             }
         }
-        Commands::Open { index } => {
-            let stories = load_cached_stories()
-                .context("Failed to load cached stories. Run a command first to populate cache.")?;
-
-            if let Some(story) = stories.iter().find(|s| s.rank == index) {
-                if let Some(url) = &story.url {
-                    open::that(url).context("Failed to open URL in browser")?;
-                    println!("{} {}", "Opened:".green(), url);
-                } else {
-                    let hn_url = format!("{}/item?id={}", BASE_URL, story.id);
-                    open::that(&hn_url).context("Failed to open HN URL in browser")?;
-                    println!("{} {}", "Opened HN discussion:".green(), hn_url);
-                }
+        Commands::User {
+            usernames,
+            submissions,
+            comments,
+            favorites,
+            list,
+        } => {
+            let listing = submissions || comments || favorites;
+            if listing && usernames.len() > 1 {
+                bail!("--submissions, --comments and --favorites take a single username");
+            }
+            let username = &usernames[0];
+
+            if submissions {
+                list_user_stories(&scraper, "submitted", username, &list).await?;
+            } else if favorites {
+                list_user_stories(&scraper, "favorites", username, &list).await?;
+            } else if comments {
+                let comments = scraper
+                    .fetch_user_comments(username, list.page)
+                    .await
+                    .with_context(|| format!("Failed to fetch comments by {}", username))?;
+                display_comment_list(&comments);
+            } else if usernames.len() > 1 {
+                let scraper = &scraper;
+                let users =
+                    futures::future::try_join_all(usernames.iter().map(|name| async move {
+                        scraper
+                            .fetch_user(name)
+                            .await
+                            .with_context(|| format!("Failed to fetch user: {}", name))
+                    }))
+                    .await?;
+                display_users_side_by_side(&users);
             } else {
-                bail!("Story with rank {} not found in cache", index);
+                let user = scraper
+                    .fetch_user(username)
+                    .await
+                    .context(format!("Failed to fetch user: {}", username))?;
+                display_user(&user);
             }
         }
-        Commands::User { username } => {
-            scraper
-                .fetch_user(&username)
+        Commands::Hiring {
+            thread,
+            month,
+            filter,
+        } => {
+            let story = scraper
+                .find_hiring_thread(thread.title(), month)
                 .await
-                .context(format!("Failed to fetch user: {}", username))?;
+                .context("Failed to find the hiring thread")?;
+            let item = scraper
+                .fetch_full_item(&story.id)
+                .await
+                .context("Failed to fetch the hiring thread")?;
+
+            let posts: Vec<HiringPost> = item
+                .comments
+                .into_iter()
+                .filter(|c| c.depth == 0 && !c.text.is_empty())
+                .map(parse_hiring_post)
+                .collect();
+            let total = posts.len();
+            let matches: Vec<HiringPost> =
+                posts.into_iter().filter(|p| filter.matches(p)).collect();
+
+            outln!(
+                "{} {}\n",
+                story.title.bright_white().bold(),
+                format!("({} of {} posts)", matches.len(), total).bright_black()
+            );
+            display_hiring_posts(&matches, filter.grep.as_deref());
         }
         Commands::Multi {
             category,
             num_pages,
+            concurrency,
         } => {
-            let endpoint = match category.as_str() {
-                "top" => "news",
-                "new" => "newest",
-                "best" => "best",
-                "ask" => "ask",
-                "show" => "show",
-                "job" => "jobs",
-                _ => "news",
-            };
+            let endpoint = category_endpoint(&category).unwrap_or("news");
 
-            let pages: Vec<usize> = (1..=num_pages).collect();
-            let all_stories = scraper
-                .fetch_multiple_pages(endpoint, pages)
-                .await
-                .context("Failed to fetch multiple pages")?;
+            let mut pages = scraper.fetch_pages(endpoint, 1..=num_pages, concurrency);
+            let mut fetched: Vec<Story> = Vec::new();
+            let mut failed = 0;
 
-            let flattened: Vec<Story> = all_stories.into_iter().flatten().collect();
-            save_stories(&flattened)?;
-            display_stories(&flattened);
+            while let Some((page, result)) = pages.next().await {
+                match result {
+                    Ok(stories) => {
+                        display_stories(&stories);
+                        save_listing(
+                            endpoint,
+                            page,
+                            &stories,
+                            scraper.offline_age().unwrap_or_default(),
+                        )?;
+                        fetched.extend(stories);
+                    }
+                    Err(e) => {
+                        failed += 1;
+                        eprintln!("{} page {}: {:#}\n", "Warning:".yellow().bold(), page, e);
+                    }
+                }
+            }
+
+            if fetched.is_empty() {
+                bail!("Failed to fetch any of the {} pages", num_pages);
+            }
+            save_stories(&fetched, scraper.offline_age().unwrap_or_default())?;
 
-            println!(
-                "\n{} Fetched {} stories from {} pages in parallel",
+            outln!(
+                "\n{} Fetched {} stories from {} of {} pages (concurrency {})",
                 "✓".green(),
-                flattened.len().to_string().bright_white().bold(),
-                num_pages.to_string().bright_white().bold()
+                fetched.len().to_string().bright_white().bold(),
+                (num_pages - failed).to_string().bright_white().bold(),
+                num_pages.to_string().bright_white().bold(),
+                concurrency.max(1)
             );
         }
+        Commands::Doctor => doctor::run(&scraper, cache_mode).await?,
+        Commands::Cache { action } => cache_command(action)?,
+        Commands::Serve { bind } => {
+            return server::serve(scraper, &bind).await;
+        }
+    }
+
+    if let Some(age) = scraper.offline_age() {
+        outln!(
+            "{}",
+            format!("Offline: showing data cached {}", format_age(age)).bright_black()
+        );
     }
 
     Ok(())
@@ -0,0 +1,360 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone)]
+pub struct Story {
+    pub rank: usize,
+    pub id: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub points: Option<usize>,
+    pub author: Option<String>,
+    pub comments: Option<usize>,
+    pub posted: Option<DateTime<Utc>>,
+    pub kind: StoryKind,
+    pub flags: BTreeSet<StoryFlag>,
+}
+
+/// What a story is. Listings don't say, so it comes from the title ("Ask HN:",
+/// "Show HN:", "Poll:") and from jobs having only an age in their subtext.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum StoryKind {
+    #[default]
+    Story,
+    Ask,
+    Show,
+    Poll,
+    Job,
+}
+
+impl StoryKind {
+    /// `has_age` tells whether the subtext showed an age. A job's subtext has
+    /// only that, while rows that lost their subtext have none, so without it
+    /// only a YC or "hiring" title makes a row a job.
+    pub fn classify(
+        title: &str,
+        points: Option<usize>,
+        author: Option<&str>,
+        has_age: bool,
+    ) -> Self {
+        let title = title.to_ascii_lowercase();
+        let job_title = title.contains("(yc ") || title.contains("hiring");
+        if points.is_none() && author.is_none() && (has_age || job_title) {
+            StoryKind::Job
+        } else if title.starts_with("poll:")
+            || title.starts_with("ask hn: poll")
+            || title.contains("(poll)")
+        {
+            StoryKind::Poll
+        } else if title.starts_with("ask hn") || title.starts_with("tell hn") {
+            StoryKind::Ask
+        } else if title.starts_with("show hn") {
+            StoryKind::Show
+        } else {
+            StoryKind::Story
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StoryKind::Story => "story",
+            StoryKind::Ask => "ask",
+            StoryKind::Show => "show",
+            StoryKind::Poll => "poll",
+            StoryKind::Job => "job",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            StoryKind::Story,
+            StoryKind::Ask,
+            StoryKind::Show,
+            StoryKind::Poll,
+            StoryKind::Job,
+        ]
+        .into_iter()
+        .find(|kind| kind.name() == name)
+    }
+}
+
+/// Markers HN prints next to a title ("[dead]", "[flagged]", "[dupe]") and
+/// the "[pdf]"/"[video]" suffixes submitters add to titles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StoryFlag {
+    Dead,
+    Flagged,
+    Dupe,
+    Pdf,
+    Video,
+}
+
+impl StoryFlag {
+    const ALL: [StoryFlag; 5] = [
+        StoryFlag::Dead,
+        StoryFlag::Flagged,
+        StoryFlag::Dupe,
+        StoryFlag::Pdf,
+        StoryFlag::Video,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StoryFlag::Dead => "dead",
+            StoryFlag::Flagged => "flagged",
+            StoryFlag::Dupe => "dupe",
+            StoryFlag::Pdf => "pdf",
+            StoryFlag::Video => "video",
+        }
+    }
+
+    /// Flags from a title and the text HN shows around it (`marks`).
+    pub fn detect(title: &str, marks: &str) -> BTreeSet<StoryFlag> {
+        let title = title.trim_end().to_ascii_lowercase();
+        let marks = marks.to_ascii_lowercase();
+        StoryFlag::ALL
+            .into_iter()
+            .filter(|flag| {
+                let marker = format!("[{}]", flag.name());
+                match flag {
+                    StoryFlag::Pdf | StoryFlag::Video => title.ends_with(&marker),
+                    _ => marks.contains(&marker),
+                }
+            })
+            .collect()
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        StoryFlag::ALL.into_iter().find(|flag| flag.name() == name)
+    }
+}
+
+impl Story {
+    pub fn is_dead(&self) -> bool {
+        self.flags.contains(&StoryFlag::Dead)
+    }
+
+    pub fn to_cache_line(&self) -> String {
+        format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            self.rank,
+            self.id,
+            self.title.replace('|', "∣"),
+            self.url.as_deref().unwrap_or(""),
+            self.points.map(|p| p.to_string()).unwrap_or_default(),
+            self.author.as_deref().unwrap_or(""),
+            self.comments.map(|c| c.to_string()).unwrap_or_default(),
+            self.posted
+                .map(|t| t.timestamp().to_string())
+                .unwrap_or_default(),
+            self.kind.name(),
+            self.flags
+                .iter()
+                .map(|flag| flag.name())
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+
+    pub fn from_cache_line(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split('|').collect();
+        // Caches written before timestamps were stored have 7 fields, before
+        // kinds and flags 8; those are worked out from the title again.
+        if ![7, 8, 10].contains(&parts.len()) {
+            return None;
+        }
+
+        let title = parts[2].replace('∣', "|");
+        let points = parts[4].parse().ok();
+        let author = (!parts[5].is_empty()).then(|| parts[5].to_string());
+        let posted = parts
+            .get(7)
+            .and_then(|t| t.parse().ok())
+            .and_then(|secs| DateTime::from_timestamp(secs, 0));
+        let (kind, flags) = match parts.get(8..10) {
+            Some([kind, flags]) => (
+                StoryKind::from_name(kind)?,
+                flags
+                    .split(',')
+                    .filter(|name| !name.is_empty())
+                    .map(StoryFlag::from_name)
+                    .collect::<Option<_>>()?,
+            ),
+            _ => (
+                StoryKind::classify(&title, points, author.as_deref(), posted.is_some()),
+                StoryFlag::detect(&title, ""),
+            ),
+        };
+
+        Some(Story {
+            rank: parts[0].parse().ok()?,
+            id: parts[1].to_string(),
+            url: if parts[3].is_empty() {
+                None
+            } else {
+                Some(parts[3].to_string())
+            },
+            points,
+            author,
+            comments: parts[6].parse().ok(),
+            posted,
+            title,
+            kind,
+            flags,
+        })
+    }
+}
+
+/// A job post from the `jobs` listing, with the details HN only puts in
+/// its title, e.g. "Acme (YC S21) Is Hiring a Senior Engineer (Remote, US)".
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub story: Story,
+    pub company: Option<String>,
+    /// The YC batch without the "YC" prefix, e.g. "S21".
+    pub batch: Option<String>,
+    pub role: Option<String>,
+    pub location: Option<String>,
+    pub remote: bool,
+}
+
+/// A single item page: the story header plus its flattened comment tree.
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub id: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub points: Option<usize>,
+    pub author: Option<String>,
+    pub posted: Option<DateTime<Utc>>,
+    pub kind: StoryKind,
+    pub flags: BTreeSet<StoryFlag>,
+    pub text: Option<String>,
+    /// Empty unless the item is a poll.
+    pub poll: Vec<PollOption>,
+    pub comments: Vec<Comment>,
+}
+
+impl Item {
+    /// The parents of the comment at `idx`, outermost first. Comments are in
+    /// page order, so each parent is the closest earlier comment one level up.
+    pub fn ancestors(&self, idx: usize) -> Vec<&Comment> {
+        let mut chain = Vec::new();
+        let mut depth = self.comments[idx].depth;
+        for comment in self.comments[..idx].iter().rev() {
+            if depth == 0 {
+                break;
+            }
+            if comment.depth < depth {
+                chain.push(comment);
+                depth = comment.depth;
+            }
+        }
+        chain.reverse();
+        chain
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PollOption {
+    pub id: String,
+    pub text: String,
+    pub points: Option<usize>,
+}
+
+/// A comment in page order; `depth` is HN's indent level (0 = top-level).
+/// On comment listings (`newcomments`, `threads`) it also links to its story.
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub id: String,
+    pub author: Option<String>,
+    pub posted: Option<DateTime<Utc>>,
+    pub depth: usize,
+    pub text: String,
+    pub story_id: Option<String>,
+    pub story_title: Option<String>,
+}
+
+/// A top-level post of a monthly "Who is hiring?" style thread, with the
+/// fields read from its `Company | Location | Role | REMOTE` header line.
+#[derive(Debug, Clone)]
+pub struct HiringPost {
+    pub comment: Comment,
+    pub company: Option<String>,
+    pub role: Option<String>,
+    pub location: Option<String>,
+    pub remote: bool,
+    pub onsite: bool,
+    pub visa: bool,
+    pub url: Option<String>,
+}
+
+/// The main text of a story's linked page, extracted for reader mode.
+#[derive(Debug, Clone)]
+pub struct Article {
+    pub url: String,
+    pub text: String,
+}
+
+impl Article {
+    pub fn word_count(&self) -> usize {
+        self.text.split_whitespace().count()
+    }
+
+    /// Minutes at 230 words per minute, rounded up.
+    pub fn reading_minutes(&self) -> usize {
+        self.word_count().div_ceil(230).max(1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct User {
+    pub id: String,
+    pub created: Option<NaiveDate>,
+    pub karma: Option<i64>,
+    /// Rendered as plain text (paragraphs separated by blank lines).
+    pub about: Option<String>,
+    /// Only known when the profile page shows a count next to its listing links.
+    pub submissions: Option<usize>,
+    pub comments: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_lines_round_trip() {
+        let story = Story {
+            rank: 3,
+            id: "41000003".to_string(),
+            title: "A | B".to_string(),
+            url: Some("https://example.com/".to_string()),
+            points: Some(120),
+            author: Some("alice".to_string()),
+            comments: None,
+            posted: DateTime::from_timestamp(1_790_000_000, 0),
+            kind: StoryKind::Show,
+            flags: BTreeSet::from([StoryFlag::Flagged, StoryFlag::Pdf]),
+        };
+
+        let cached = Story::from_cache_line(&story.to_cache_line()).unwrap();
+        assert_eq!(cached.title, story.title);
+        assert_eq!(cached.url, story.url);
+        assert_eq!(cached.comments, None);
+        assert_eq!(cached.posted, story.posted);
+        assert_eq!(cached.kind, story.kind);
+        assert_eq!(cached.flags, story.flags);
+    }
+
+    #[test]
+    fn older_cache_lines_get_kinds_and_flags_from_the_title() {
+        let cached = Story::from_cache_line("2|7|Show HN: A paper [pdf]||10|bob|3|").unwrap();
+        assert_eq!(cached.kind, StoryKind::Show);
+        assert_eq!(cached.flags, BTreeSet::from([StoryFlag::Pdf]));
+
+        let job = Story::from_cache_line("3|8|Acme is hiring|https://acme.example||||").unwrap();
+        assert_eq!(job.kind, StoryKind::Job);
+    }
+}
@@ -0,0 +1,187 @@
+//! Sends long output through a pager. Lines written with `outln!` are held
+//! back until they no longer fit on the screen; then the pager is started and
+//! everything is streamed to it. Shorter output is printed when the pager
+//! guard is dropped. Colors and OSC 8 links are left in, so the default pager
+//! is `less -R`; without it a small built-in pager is used.
+
+use colored::*;
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::Mutex;
+
+const DEFAULT_PAGER: &str = "less -R";
+
+enum Sink {
+    /// Not a terminal or paging disabled: print as we go.
+    Direct,
+    /// Waiting to see whether the output fits on the screen.
+    Buffer {
+        text: String,
+        lines: usize,
+        height: usize,
+        command: String,
+    },
+    Pager {
+        child: Child,
+        stdin: Option<ChildStdin>,
+    },
+    /// The pager command can't be run; page at the end with the built-in one.
+    Builtin { text: String, height: usize },
+}
+
+static SINK: Mutex<Sink> = Mutex::new(Sink::Direct);
+
+/// Writes one line of command output; use it instead of `println!`.
+macro_rules! outln {
+    () => {
+        $crate::pager::write_line(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::pager::write_line(format_args!($($arg)*))
+    };
+}
+pub(crate) use outln;
+
+/// Flushes or finishes paging when dropped, also on early error returns.
+pub struct PagerGuard;
+
+impl Drop for PagerGuard {
+    fn drop(&mut self) {
+        finish();
+    }
+}
+
+/// Starts collecting output for paging when `enabled` and stdout is a
+/// terminal. `command` is the configured pager, which wins over `$PAGER`.
+pub fn start(enabled: bool, command: Option<String>) -> PagerGuard {
+    let height = terminal_size::terminal_size().map(|(_, height)| usize::from(height.0));
+    if let (true, true, Some(height)) = (enabled, io::stdout().is_terminal(), height) {
+        let command = command
+            .or_else(|| std::env::var("PAGER").ok())
+            .filter(|c| !c.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+        *lock() = Sink::Buffer {
+            text: String::new(),
+            lines: 0,
+            height,
+            command,
+        };
+    }
+    PagerGuard
+}
+
+pub fn write_line(args: fmt::Arguments) {
+    let mut sink = lock();
+    match &mut *sink {
+        Sink::Direct => println!("{}", args),
+        Sink::Buffer {
+            text,
+            lines,
+            height,
+            command,
+        } => {
+            let line = args.to_string();
+            *lines += line.lines().count().max(1);
+            text.push_str(&line);
+            text.push('\n');
+
+            if *lines >= *height {
+                let text = std::mem::take(text);
+                let height = *height;
+                let pager = spawn(command);
+                *sink = match pager {
+                    Some((child, mut stdin)) => {
+                        let stdin = stdin.write_all(text.as_bytes()).is_ok().then_some(stdin);
+                        Sink::Pager { child, stdin }
+                    }
+                    None => Sink::Builtin { text, height },
+                };
+            }
+        }
+        Sink::Pager { stdin, .. } => {
+            // Once the pager has quit, the rest of the output is dropped.
+            if let Some(pipe) = stdin
+                && writeln!(pipe, "{}", args).is_err()
+            {
+                *stdin = None;
+            }
+        }
+        Sink::Builtin { text, .. } => {
+            text.push_str(&args.to_string());
+            text.push('\n');
+        }
+    }
+}
+
+fn finish() {
+    let sink = std::mem::replace(&mut *lock(), Sink::Direct);
+    match sink {
+        Sink::Direct => {}
+        Sink::Buffer { text, .. } => print!("{}", text),
+        Sink::Pager { mut child, stdin } => {
+            drop(stdin);
+            let _ = child.wait();
+        }
+        Sink::Builtin { text, height } => builtin_pager(&text, height),
+    }
+    let _ = io::stdout().flush();
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Sink> {
+    SINK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn spawn(command: &str) -> Option<(Child, ChildStdin)> {
+    let mut words = command.split_whitespace();
+    let mut child = Command::new(words.next()?)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()
+        .ok()?;
+    let stdin = child.stdin.take()?;
+    Some((child, stdin))
+}
+
+/// Shows a screenful at a time; Enter shows the next one, `q` quits.
+fn builtin_pager(text: &str, height: usize) {
+    let lines: Vec<&str> = text.lines().collect();
+    let page = height.saturating_sub(1).max(1);
+    let interactive = io::stdin().is_terminal();
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+
+    let mut start = 0;
+    while start < lines.len() {
+        let end = if interactive {
+            (start + page).min(lines.len())
+        } else {
+            lines.len()
+        };
+        for line in &lines[start..end] {
+            if writeln!(stdout, "{}", line).is_err() {
+                return;
+            }
+        }
+        start = end;
+        if start == lines.len() {
+            break;
+        }
+
+        let prompt = format!(
+            "-- {}% -- Enter: next page, q: quit ",
+            start * 100 / lines.len()
+        );
+        let _ = write!(stdout, "{}", prompt.reversed());
+        let _ = stdout.flush();
+
+        let mut answer = String::new();
+        let quit = stdin.read_line(&mut answer).map_or(true, |n| n == 0)
+            || answer.trim().eq_ignore_ascii_case("q");
+        // Erase the prompt line left behind by the Enter key.
+        let _ = write!(stdout, "\x1b[1A\r\x1b[2K");
+        if quit {
+            break;
+        }
+    }
+}
@@ -0,0 +1,1119 @@
+use crate::model::{Comment, HiringPost, Item, Job, PollOption, Story, StoryFlag, StoryKind, User};
+use crate::render;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use scraper::{ElementRef, Html};
+
+pub const BASE_URL: &str = "https://news.ycombinator.com";
+pub const ITEMS_PER_PAGE: usize = 30;
+
+// Safe selector init
+macro_rules! safe_selector {
+    ($name:ident, $pattern:expr) => {
+        fn $name() -> &'static scraper::Selector {
+            static CELL: std::sync::OnceLock<scraper::Selector> = std::sync::OnceLock::new();
+            CELL.get_or_init(|| {
+                scraper::Selector::parse($pattern)
+                    .unwrap_or_else(|_| panic!("Invalid CSS selector: {}", $pattern))
+            })
+        }
+    };
+}
+pub(crate) use safe_selector;
+
+safe_selector!(row_selector, "tr.athing");
+safe_selector!(subtext_selector, "tr > td.subtext");
+safe_selector!(title_selector, "span.titleline > a");
+safe_selector!(rank_selector, "span.rank");
+safe_selector!(score_selector, "span.score");
+safe_selector!(age_selector, "span.age");
+safe_selector!(user_selector, "a.hnuser");
+safe_selector!(link_selector, "a");
+safe_selector!(title_display_selector, "span.titleline");
+safe_selector!(text_selector, "div.toptext");
+safe_selector!(comment_selector, "tr.athing.comtr");
+safe_selector!(comhead_selector, "span.comhead");
+safe_selector!(commtext_selector, "div.commtext");
+safe_selector!(comment_body_selector, "div.comment");
+safe_selector!(ind_selector, "td.ind");
+safe_selector!(tr_selector, "tr");
+safe_selector!(td_selector, "td");
+safe_selector!(onstory_selector, "span.onstory a");
+safe_selector!(morelink_selector, "a.morelink");
+safe_selector!(pollopt_text_selector, "td.comment");
+
+/// Turns a relative HN href (`item?id=1`) into an absolute URL.
+pub fn absolute_url(href: &str) -> String {
+    if href.starts_with("http") {
+        href.to_string()
+    } else {
+        format!("{}/{}", BASE_URL, href.trim_start_matches('/'))
+    }
+}
+
+/// Every selector the parsers rely on with the number of nodes it matches
+/// in `html`, for `hn doctor`.
+pub fn selector_counts(html: &str) -> Vec<(&'static str, usize)> {
+    let document = Html::parse_document(html);
+    [
+        ("tr.athing", row_selector()),
+        ("tr > td.subtext", subtext_selector()),
+        ("span.titleline > a", title_selector()),
+        ("span.rank", rank_selector()),
+        ("span.score", score_selector()),
+        ("span.age", age_selector()),
+        ("a.hnuser", user_selector()),
+        ("a.morelink", morelink_selector()),
+        ("div.toptext", text_selector()),
+        ("tr.athing.comtr", comment_selector()),
+        ("span.comhead", comhead_selector()),
+        ("div.commtext", commtext_selector()),
+        ("td.ind", ind_selector()),
+    ]
+    .into_iter()
+    .map(|(css, selector)| (css, document.select(selector).count()))
+    .collect()
+}
+
+/// An element's text with entities decoded (`inner_html` would keep `&amp;`).
+fn element_text(element: ElementRef) -> String {
+    element.text().collect()
+}
+
+/// Number at the start of texts like "120 points" or "1,045&nbsp;comments"
+/// (`char::is_whitespace` includes the non-breaking space).
+fn parse_leading_number(text: &str) -> Option<usize> {
+    text.split_whitespace()
+        .next()
+        .and_then(|s| s.replace(',', "").parse().ok())
+}
+
+/// Stories without comments link to "discuss" instead of "0 comments".
+fn comment_count(subtext: ElementRef) -> Option<usize> {
+    for link in subtext.select(link_selector()) {
+        let text = element_text(link);
+        if text.contains("comment") {
+            return parse_leading_number(&text);
+        }
+        if text.trim() == "discuss" {
+            return Some(0);
+        }
+    }
+    None
+}
+
+/// Reads the exact time from `span.age`'s title ("2024-03-15T12:34:56 1710505696",
+/// older pages have only the ISO part), falling back to the "3 hours ago" text.
+fn parse_posted(age: ElementRef) -> Option<DateTime<Utc>> {
+    let title = age.value().attr("title").unwrap_or_default();
+    let mut parts = title.split_whitespace();
+    let iso = parts.next();
+
+    parts
+        .next()
+        .and_then(|epoch| epoch.parse().ok())
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .or_else(|| {
+            iso.and_then(|iso| NaiveDateTime::parse_from_str(iso, "%Y-%m-%dT%H:%M:%S").ok())
+                .map(|time| time.and_utc())
+        })
+        .or_else(|| parse_relative_age(&age.text().collect::<String>()))
+}
+
+fn parse_relative_age(text: &str) -> Option<DateTime<Utc>> {
+    let mut words = text.split_whitespace();
+    let value: i64 = words.next()?.parse().ok()?;
+    let unit = match words.next()?.trim_end_matches('s') {
+        "minute" => TimeDelta::try_minutes(value)?,
+        "hour" => TimeDelta::try_hours(value)?,
+        "day" => TimeDelta::try_days(value)?,
+        "month" => TimeDelta::try_days(value * 30)?,
+        "year" => TimeDelta::try_days(value * 365)?,
+        _ => return None,
+    };
+    Some(Utc::now() - unit)
+}
+
+/// Parses a story listing page (`news`, `newest`, `ask`, ...).
+pub fn parse_stories(html: &str, page: usize) -> Vec<Story> {
+    let document = Html::parse_document(html);
+    let mut stories = Vec::with_capacity(ITEMS_PER_PAGE);
+
+    for (idx, row) in document.select(row_selector()).enumerate() {
+        let id = row.value().attr("id").unwrap_or("unknown").to_string();
+
+        let rank = row
+            .select(rank_selector())
+            .next()
+            .and_then(|r| element_text(r).trim_end_matches('.').parse().ok())
+            .unwrap_or((page - 1) * ITEMS_PER_PAGE + idx + 1);
+
+        let title_elem = row.select(title_selector()).next();
+        let title = title_elem.map(element_text).unwrap_or_default();
+
+        if title.is_empty() {
+            continue;
+        }
+
+        let url = title_elem
+            .and_then(|e| e.value().attr("href"))
+            .map(absolute_url);
+
+        let mut points = None;
+        let mut author = None;
+        let mut comments = None;
+        let mut posted = None;
+
+        if let Some(subtext) = subtext_for(row, &id) {
+            if let Some(score) = subtext.select(score_selector()).next() {
+                points = parse_leading_number(&element_text(score));
+            }
+
+            if let Some(user) = subtext.select(user_selector()).next() {
+                author = Some(element_text(user));
+            }
+
+            if let Some(age_elem) = subtext.select(age_selector()).next() {
+                posted = parse_posted(age_elem);
+            }
+
+            comments = comment_count(subtext);
+        }
+
+        let marks = row
+            .select(title_display_selector())
+            .next()
+            .map(title_marks)
+            .unwrap_or_default();
+
+        stories.push(Story {
+            rank,
+            id,
+            kind: StoryKind::classify(&title, points, author.as_deref(), posted.is_some()),
+            flags: StoryFlag::detect(&title, &marks),
+            title,
+            url,
+            points,
+            author,
+            comments,
+            posted,
+        });
+    }
+
+    stories
+}
+
+/// The subtext (points, author, age, comments) of a story row is in the row
+/// right after it. Rows without one (e.g. ads) get none, rather than the next
+/// story's: a subtext linking to a different item is not this row's.
+fn subtext_for<'a>(row: ElementRef<'a>, id: &str) -> Option<ElementRef<'a>> {
+    let next = row.next_siblings().find_map(ElementRef::wrap)?;
+    let subtext = next.select(subtext_selector()).next()?;
+
+    let mut items = subtext
+        .select(link_selector())
+        .filter_map(|a| a.value().attr("href")?.strip_prefix("item?id="))
+        .peekable();
+    let is_own = items.peek().is_none() || items.any(|item_id| item_id == id);
+    is_own.then_some(subtext)
+}
+
+/// The text HN puts in the title cell next to the title line, where it marks
+/// stories "[dead]", "[flagged]" or "[dupe]".
+fn title_marks(title_line: ElementRef) -> String {
+    title_line
+        .parent()
+        .and_then(ElementRef::wrap)
+        .map(|cell| element_text(cell).replacen(&element_text(title_line), "", 1))
+        .unwrap_or_default()
+}
+
+/// Parses an item page into its header, optional text and comments.
+pub fn parse_item(html: &str, id: &str) -> Item {
+    let document = Html::parse_document(html);
+
+    let mut title = String::new();
+    let mut url = None;
+    let mut marks = String::new();
+    if let Some(title_elem) = document.select(title_display_selector()).next()
+        && let Some(link) = title_elem.select(link_selector()).next()
+    {
+        title = element_text(link);
+        url = link.value().attr("href").map(absolute_url);
+        marks = title_marks(title_elem);
+    }
+
+    let mut points = None;
+    let mut author = None;
+    let mut posted = None;
+    if let Some(subtext) = document.select(subtext_selector()).next() {
+        points = subtext
+            .select(score_selector())
+            .next()
+            .and_then(|s| parse_leading_number(&element_text(s)));
+        author = subtext.select(user_selector()).next().map(element_text);
+        posted = subtext.select(age_selector()).next().and_then(parse_posted);
+    }
+
+    let text = document
+        .select(text_selector())
+        .next()
+        .map(render::html_to_text)
+        .filter(|t| !t.is_empty());
+
+    let comments = document
+        .select(comment_selector())
+        .map(parse_comment)
+        .collect();
+
+    let poll = parse_poll_options(&document);
+    let kind = if poll.is_empty() {
+        StoryKind::classify(&title, points, author.as_deref(), posted.is_some())
+    } else {
+        StoryKind::Poll
+    };
+
+    Item {
+        id: id.to_string(),
+        flags: StoryFlag::detect(&title, &marks),
+        kind,
+        title,
+        url,
+        points,
+        author,
+        posted,
+        text,
+        poll,
+        comments,
+    }
+}
+
+/// Poll options are `athing` rows (class `pollopt` on newer pages) whose text
+/// sits in `td.comment`; the score is in the row itself or the one after it.
+fn parse_poll_options(document: &Html) -> Vec<PollOption> {
+    document
+        .select(row_selector())
+        .filter(|row| {
+            let classes = row.value().attr("class").unwrap_or_default();
+            !classes.contains("comtr")
+                && (classes.contains("pollopt")
+                    || row.select(pollopt_text_selector()).next().is_some())
+        })
+        .filter_map(|row| {
+            let text = row
+                .select(pollopt_text_selector())
+                .next()
+                .map(render::html_to_text)
+                .filter(|t| !t.is_empty())?;
+
+            let points = row
+                .select(score_selector())
+                .next()
+                .or_else(|| {
+                    row.next_siblings()
+                        .filter_map(ElementRef::wrap)
+                        .next()
+                        .and_then(|next| next.select(score_selector()).next())
+                })
+                .and_then(|score| parse_leading_number(&element_text(score)));
+
+            Some(PollOption {
+                id: row.value().attr("id").unwrap_or_default().to_string(),
+                text,
+                points,
+            })
+        })
+        .collect()
+}
+
+fn parse_comment(row: ElementRef) -> Comment {
+    let depth = row
+        .select(ind_selector())
+        .next()
+        .and_then(|td| td.value().attr("indent"))
+        .and_then(|i| i.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let comhead = row.select(comhead_selector()).next();
+    let author = comhead
+        .and_then(|c| c.select(user_selector()).next())
+        .map(element_text);
+    let posted = comhead
+        .and_then(|c| c.select(age_selector()).next())
+        .and_then(parse_posted);
+
+    // Deleted comments have no `commtext`, just "[deleted]" in the comment div.
+    let text = row
+        .select(commtext_selector())
+        .next()
+        .or_else(|| row.select(comment_body_selector()).next())
+        .map(render::html_to_text)
+        .unwrap_or_default();
+
+    let story_link = row.select(onstory_selector()).next();
+    let story_id = story_link
+        .and_then(|a| a.value().attr("href"))
+        .and_then(|href| href.split("id=").nth(1))
+        .map(|id| id.split('&').next().unwrap_or(id).to_string());
+    let story_title = story_link.map(element_text);
+
+    Comment {
+        id: row.value().attr("id").unwrap_or_default().to_string(),
+        author,
+        posted,
+        depth,
+        text,
+        story_id,
+        story_title,
+    }
+}
+
+/// Parses a comment listing (`newcomments`, `bestcomments`, `threads`).
+pub fn parse_comment_list(html: &str) -> Vec<Comment> {
+    let document = Html::parse_document(html);
+
+    document
+        .select(row_selector())
+        .filter(|row| row.select(comhead_selector()).next().is_some())
+        .map(parse_comment)
+        .collect()
+}
+
+/// Absolute URL of a listing's "More" link, if there is a next page.
+pub fn more_link(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+
+    document
+        .select(morelink_selector())
+        .next()
+        .and_then(|a| a.value().attr("href"))
+        .map(absolute_url)
+}
+
+/// Parses a user profile page. Returns `None` when no profile fields are present.
+pub fn parse_user(html: &str, username: &str) -> Option<User> {
+    let document = Html::parse_document(html);
+
+    let mut user = User {
+        id: username.to_string(),
+        created: None,
+        karma: None,
+        about: None,
+        submissions: None,
+        comments: None,
+    };
+    let mut found_data = false;
+
+    for row in document.select(tr_selector()) {
+        let cells: Vec<_> = row.select(td_selector()).collect();
+
+        if cells.len() != 2 {
+            continue;
+        }
+
+        let field = cells[0].text().collect::<String>().trim().to_string();
+        let value_text = cells[1].text().collect::<String>().trim().to_string();
+
+        match field.trim_end_matches(':') {
+            "user" if field.ends_with(':') => {
+                user.id = value_text;
+                found_data = true;
+            }
+            "created" if field.ends_with(':') => {
+                user.created = parse_created(cells[1], &value_text);
+                found_data = true;
+            }
+            "karma" if field.ends_with(':') => {
+                user.karma = value_text.replace(',', "").parse().ok();
+                found_data = true;
+            }
+            "about" if field.ends_with(':') => {
+                user.about = Some(render::html_to_text(cells[1])).filter(|a| !a.is_empty());
+                found_data = true;
+            }
+            // The listing links below the profile, e.g. "submissions".
+            "" if value_text.starts_with("submissions") => {
+                user.submissions = leading_digits(&value_text);
+            }
+            "" if value_text.starts_with("comments") => {
+                user.comments = leading_digits(&value_text);
+            }
+            _ => {}
+        }
+    }
+
+    found_data.then_some(user)
+}
+
+/// HN links the created date to that day's front page (`front?day=2010-03-03`);
+/// falls back to an epoch in the link or the displayed "March 3, 2010".
+fn parse_created(cell: ElementRef, text: &str) -> Option<NaiveDate> {
+    let href = cell
+        .select(link_selector())
+        .next()
+        .and_then(|a| a.value().attr("href"))
+        .unwrap_or_default();
+
+    let from_day = href
+        .split("day=")
+        .nth(1)
+        .and_then(|day| day.split('&').next())
+        .and_then(|day| day.parse().ok());
+    let from_epoch = || {
+        href.split(|c: char| !c.is_ascii_digit())
+            .find(|digits| digits.len() >= 9)
+            .and_then(|digits| digits.parse().ok())
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .map(|time| time.date_naive())
+    };
+
+    from_day
+        .or_else(from_epoch)
+        .or_else(|| NaiveDate::parse_from_str(text, "%B %d, %Y").ok())
+}
+
+/// First number in a text such as "submissions (123)".
+fn leading_digits(text: &str) -> Option<usize> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .find(|digits| !digits.is_empty())
+        .and_then(|digits| digits.parse().ok())
+}
+
+/// Separators HN job titles use between the role and the location.
+const JOB_SEPARATORS: [&str; 5] = [" – ", " — ", " - ", " | ", "; "];
+
+/// Reads company, YC batch, role and location out of a job post title.
+/// Titles are free text, so every part is best effort.
+pub fn parse_job(story: Story) -> Job {
+    let title = story.title.trim();
+    let lower = title.to_ascii_lowercase();
+
+    let mut company = None;
+    let mut batch = None;
+    let mut rest = title;
+    if let Some(start) = lower.find("(yc ")
+        && let Some(len) = lower[start..].find(')')
+    {
+        company = Some(title[..start].trim());
+        batch = Some(title[start + 4..start + len].trim().to_string());
+        rest = &title[start + len + 1..];
+    } else if let Some(pos) = lower.find(" is hiring").or_else(|| lower.find(" hiring")) {
+        company = Some(title[..pos].trim());
+        rest = &title[pos..];
+    }
+
+    let rest = rest.trim();
+    // Without a hiring phrase the title is an announcement, not a role.
+    let hiring = ["is hiring", "hiring"].into_iter().find_map(|phrase| {
+        rest.get(..phrase.len())
+            .filter(|start| start.eq_ignore_ascii_case(phrase))
+            .map(|_| &rest[phrase.len()..])
+    });
+    let (role, location) = hiring.map_or(("", None), |rest| {
+        split_job_location(
+            rest.trim_start_matches([' ', ':', '-', '–', '—', '|'])
+                .trim(),
+        )
+    });
+
+    let article = ["a ", "an "].into_iter().find(|article| {
+        role.get(..article.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(article))
+    });
+    let role = article
+        .map_or(role, |article| &role[article.len()..])
+        .trim();
+
+    Job {
+        company: company.filter(|c| !c.is_empty()).map(str::to_string),
+        batch: batch.filter(|b| !b.is_empty()),
+        role: (!role.is_empty()).then(|| role.to_string()),
+        location: location.map(str::to_string),
+        remote: lower.contains("remote"),
+        story,
+    }
+}
+
+/// Splits "Designer – NYC", "Senior Engineer (Remote, US)" or "Engineers in
+/// Berlin" into role and location. Separators come first, so in "Engineer -
+/// Remote (US)" the parenthesis stays part of the location.
+fn split_job_location(text: &str) -> (&str, Option<&str>) {
+    if text.eq_ignore_ascii_case("remote") {
+        return ("", Some(text));
+    }
+
+    // Separators inside parentheses, as in "(Remote - US)", don't count.
+    let outside_parens =
+        |pos: usize| text[..pos].matches('(').count() == text[..pos].matches(')').count();
+    if let Some((pos, sep)) = JOB_SEPARATORS
+        .iter()
+        .filter_map(|sep| {
+            text.match_indices(*sep)
+                .map(|(pos, _)| pos)
+                .filter(|&pos| outside_parens(pos))
+                .last()
+                .map(|pos| (pos, sep))
+        })
+        .max()
+    {
+        return (text[..pos].trim(), Some(text[pos + sep.len()..].trim()));
+    }
+
+    if let Some(inner) = text.strip_suffix(')')
+        && let Some(open) = inner.rfind('(')
+    {
+        return (inner[..open].trim(), Some(inner[open + 1..].trim()));
+    }
+
+    if let Some(pos) = text.to_ascii_lowercase().rfind(" in ") {
+        return (text[..pos].trim(), Some(text[pos + 4..].trim()));
+    }
+
+    (text, None)
+}
+
+const ROLE_WORDS: [&str; 22] = [
+    "engineer",
+    "developer",
+    "designer",
+    "manager",
+    "scientist",
+    "lead",
+    "head of",
+    "cto",
+    "sre",
+    "devops",
+    "analyst",
+    "researcher",
+    "architect",
+    "marketing",
+    "sales",
+    "product",
+    "full stack",
+    "full-stack",
+    "frontend",
+    "backend",
+    "staff",
+    "founding",
+];
+const EMPLOYMENT_WORDS: [&str; 7] = [
+    "full-time",
+    "full time",
+    "part-time",
+    "part time",
+    "contract",
+    "intern",
+    "salary",
+];
+const ONSITE_WORDS: [&str; 6] = [
+    "onsite",
+    "on-site",
+    "on site",
+    "in-office",
+    "in office",
+    "hybrid",
+];
+
+/// Reads a hiring thread post. Employers use a `Company | Location | Role |
+/// REMOTE` header line; "Who wants to be hired?" posts use `Location:` and
+/// `Remote:` lines instead. Either way every field is best effort.
+pub fn parse_hiring_post(comment: Comment) -> HiringPost {
+    let lower = comment.text.to_lowercase();
+    let visa = lower.contains("visa") && !lower.contains("no visa");
+
+    let field = |name: &str| {
+        comment.text.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().to_string())
+        })
+    };
+
+    let header = comment.text.lines().next().unwrap_or_default();
+    if !header.contains('|')
+        && let Some(location) = field("Location")
+    {
+        let remote = field("Remote").unwrap_or_default().to_lowercase();
+        return HiringPost {
+            company: None,
+            role: None,
+            location: Some(location).filter(|l| !l.is_empty()),
+            remote: remote.starts_with("yes") || remote.starts_with("only"),
+            onsite: remote.starts_with("no") || ONSITE_WORDS.iter().any(|w| remote.contains(w)),
+            visa,
+            url: None,
+            comment,
+        };
+    }
+
+    let mut parts = header.split('|').map(str::trim).filter(|p| !p.is_empty());
+    let mut company = parts.next().map(str::to_string);
+    // Freelancer threads start with "SEEKING WORK" or "SEEKING FREELANCER".
+    let seeking = company
+        .as_deref()
+        .is_some_and(|c| c.to_ascii_uppercase().starts_with("SEEKING"));
+    if seeking {
+        company = None;
+    }
+
+    let mut role = None;
+    let mut location = None;
+    let mut mode_location = None;
+    let mut url = None;
+    let mut remote = false;
+    let mut onsite = false;
+    for part in parts {
+        let lower = part.to_lowercase();
+        let is_remote = lower.contains("remote");
+        let is_onsite = ONSITE_WORDS.iter().any(|w| lower.contains(w));
+        remote |= is_remote;
+        onsite |= is_onsite;
+
+        if lower.starts_with("http") || lower.starts_with("www.") {
+            url.get_or_insert_with(|| part.to_string());
+        } else if is_remote || is_onsite {
+            // "REMOTE (US)" or "Onsite in Berlin" still name a place.
+            if part.contains(['(', ',']) || lower.contains(" in ") {
+                mode_location.get_or_insert(part);
+            }
+        } else if EMPLOYMENT_WORDS.iter().any(|w| lower.contains(w))
+            || part.contains(['$', '€', '£'])
+        {
+            continue;
+        } else if role.is_none() && ROLE_WORDS.iter().any(|w| lower.contains(w)) {
+            role = Some(part.to_string());
+        } else if location.is_none() && !(seeking && (remote || onsite)) {
+            // Freelancers list their skills after the work mode.
+            location = Some(part.to_string());
+        }
+    }
+
+    HiringPost {
+        company,
+        role,
+        location: location.or(mode_location.map(str::to_string)),
+        remote,
+        onsite,
+        visa,
+        url,
+        comment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRONT_PAGE: &str = include_str!("../tests/fixtures/hn_front.html");
+    const ASK_PAGE: &str = include_str!("../tests/fixtures/hn_ask.html");
+    const MIXED_PAGE: &str = include_str!("../tests/fixtures/hn_front_mixed.html");
+    const JOBS_PAGE: &str = include_str!("../tests/fixtures/hn_jobs.html");
+    const ITEM_PAGE: &str = include_str!("../tests/fixtures/hn_item.html");
+    const USER_PAGE: &str = include_str!("../tests/fixtures/hn_user.html");
+    const HIRING_PAGE: &str = include_str!("../tests/fixtures/hn_hiring.html");
+    const POLL_PAGE: &str = include_str!("../tests/fixtures/hn_poll.html");
+
+    fn epoch(secs: i64) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(secs, 0)
+    }
+
+    #[test]
+    fn parses_front_page_stories() {
+        let stories = parse_stories(FRONT_PAGE, 1);
+        assert_eq!(stories.len(), 4);
+
+        let story = &stories[0];
+        assert_eq!(story.rank, 1);
+        assert_eq!(story.id, "41000001");
+        assert_eq!(story.title, "Async Rust & the \"colored\" function problem");
+        assert_eq!(story.url.as_deref(), Some("https://example.com/rust-async"));
+        assert_eq!(story.points, Some(120));
+        assert_eq!(story.author.as_deref(), Some("alice"));
+        assert_eq!(story.comments, Some(1045));
+        assert_eq!(story.posted, epoch(1792317600));
+
+        assert_eq!(stories[1].points, Some(1));
+        assert_eq!(stories[3].comments, Some(1));
+    }
+
+    #[test]
+    fn discuss_link_means_no_comments() {
+        let stories = parse_stories(FRONT_PAGE, 1);
+        assert_eq!(stories[1].title, "A survey of garbage collectors [pdf]");
+        assert_eq!(stories[1].comments, Some(0));
+    }
+
+    #[test]
+    fn dead_and_flagged_stories_keep_their_metadata() {
+        let stories = parse_stories(FRONT_PAGE, 1);
+
+        let dead = &stories[2];
+        assert_eq!(dead.id, "41000003");
+        assert_eq!(dead.title, "Buy cheap watches");
+        assert_eq!(dead.author.as_deref(), Some("spammer"));
+        assert_eq!(dead.comments, Some(0));
+
+        let flagged = &stories[3];
+        assert_eq!(flagged.id, "41000004");
+        assert_eq!(flagged.title, "Something everybody is angry about");
+        assert_eq!(flagged.points, Some(48));
+        assert_eq!(flagged.author.as_deref(), Some("carol"));
+    }
+
+    #[test]
+    fn metadata_stays_with_its_story_between_job_rows() {
+        let stories = parse_stories(MIXED_PAGE, 1);
+        let summary: Vec<_> = stories
+            .iter()
+            .map(|s| (s.id.as_str(), s.points, s.author.as_deref(), s.comments))
+            .collect();
+
+        assert_eq!(
+            summary,
+            [
+                ("45000001", Some(300), Some("alice"), Some(150)),
+                ("45000002", None, None, None),
+                ("45000003", None, None, None),
+                ("45000004", Some(42), Some("bob"), Some(7)),
+                ("45000005", None, None, None),
+                ("45000006", Some(9), Some("carol"), Some(0)),
+            ]
+        );
+        assert_eq!(stories[1].posted, epoch(1792303200));
+        assert_eq!(stories[2].posted, None);
+        assert_eq!(stories[4].posted, epoch(1792242000));
+    }
+
+    #[test]
+    fn reads_kinds_and_flags() {
+        let stories = parse_stories(FRONT_PAGE, 1);
+        let flags: Vec<_> = stories
+            .iter()
+            .map(|s| s.flags.iter().map(|f| f.name()).collect::<Vec<_>>())
+            .collect();
+        assert_eq!(flags, [vec![], vec!["pdf"], vec!["dead"], vec!["flagged"]]);
+        assert!(stories.iter().all(|s| s.kind == StoryKind::Story));
+
+        let mixed = parse_stories(MIXED_PAGE, 1);
+        assert_eq!(mixed[1].kind, StoryKind::Job);
+        // No subtext at all is not enough to make a row a job.
+        assert_eq!(mixed[2].kind, StoryKind::Story);
+        assert_eq!(mixed[3].kind, StoryKind::Story);
+        assert_eq!(mixed[4].kind, StoryKind::Job);
+
+        let item = parse_item(ITEM_PAGE, "44000001");
+        assert_eq!(item.kind, StoryKind::Ask);
+        assert!(item.flags.is_empty());
+    }
+
+    #[test]
+    fn parses_ask_page_with_relative_links() {
+        let stories = parse_stories(ASK_PAGE, 2);
+        assert_eq!(stories.len(), 2);
+
+        assert_eq!(stories[0].rank, 31);
+        assert_eq!(
+            stories[0].title,
+            "Ask HN: What's your favorite debugging trick?"
+        );
+        assert_eq!(
+            stories[0].url.as_deref(),
+            Some("https://news.ycombinator.com/item?id=43000001")
+        );
+        assert_eq!(stories[0].comments, Some(187));
+        assert_eq!(stories[0].kind, StoryKind::Ask);
+        assert_eq!(stories[1].kind, StoryKind::Poll);
+        assert_eq!(stories[1].comments, Some(0));
+    }
+
+    #[test]
+    fn jobs_have_no_score_author_or_comments() {
+        let stories = parse_stories(JOBS_PAGE, 1);
+        assert_eq!(stories.len(), 2);
+
+        for (idx, story) in stories.iter().enumerate() {
+            assert_eq!(story.rank, idx + 1);
+            assert_eq!(story.points, None);
+            assert_eq!(story.author, None);
+            assert_eq!(story.comments, None);
+        }
+        assert_eq!(stories[0].posted, epoch(1792249200));
+
+        let job = parse_job(stories[1].clone());
+        assert_eq!(job.company.as_deref(), Some("Widgets Inc."));
+        assert_eq!(job.batch.as_deref(), Some("W24"));
+        assert_eq!(job.location.as_deref(), Some("San Francisco"));
+
+        let mut story = stories[0].clone();
+        story.title = story.title.replace("Hiring a", "Hiring A");
+        let job = parse_job(story);
+        assert_eq!(job.role.as_deref(), Some("Senior Rust Engineer"));
+    }
+
+    /// The top-level posts of the saved hiring thread, in page order.
+    fn hiring_posts() -> Vec<HiringPost> {
+        parse_item(HIRING_PAGE, "46000001")
+            .comments
+            .into_iter()
+            .filter(|c| c.depth == 0)
+            .map(parse_hiring_post)
+            .collect()
+    }
+
+    #[test]
+    fn parses_hiring_headers() {
+        let posts = hiring_posts();
+        assert_eq!(posts.len(), 9);
+
+        let acme = &posts[0];
+        assert_eq!(acme.company.as_deref(), Some("Acme"));
+        assert_eq!(acme.role.as_deref(), Some("Senior Rust Engineer"));
+        assert_eq!(acme.location.as_deref(), Some("Berlin, Germany"));
+        assert_eq!(acme.url.as_deref(), Some("https://acme.example/jobs"));
+        assert!(acme.remote && acme.onsite && !acme.visa);
+
+        let widgets = &posts[1];
+        assert_eq!(widgets.role.as_deref(), Some("Designer"));
+        assert_eq!(widgets.location.as_deref(), Some("NYC"));
+        assert!(!widgets.remote && widgets.onsite && widgets.visa);
+
+        assert_eq!(posts[2].location.as_deref(), Some("San Francisco"));
+        assert!(!posts[2].visa);
+    }
+
+    #[test]
+    fn employer_headers_win_over_location_lines() {
+        let post = &hiring_posts()[3];
+        assert_eq!(post.company.as_deref(), Some("Acme Remote"));
+        assert_eq!(post.role.as_deref(), Some("Engineer"));
+        assert!(post.remote && !post.onsite);
+    }
+
+    #[test]
+    fn parses_who_wants_to_be_hired_posts() {
+        let posts = hiring_posts();
+        let summary: Vec<_> = posts[4..7]
+            .iter()
+            .map(|p| {
+                (
+                    p.company.as_deref(),
+                    p.location.as_deref(),
+                    p.remote,
+                    p.onsite,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (None, Some("Lisbon, Portugal"), true, false),
+                (None, Some("Austin, TX"), false, true),
+                (None, Some("Toronto"), true, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn freelancer_skills_are_not_a_location() {
+        let posts = hiring_posts();
+        assert_eq!(posts[7].company, None);
+        assert_eq!(posts[7].location, None);
+        assert!(posts[7].remote);
+        assert_eq!(posts[8].location.as_deref(), Some("Berlin"));
+    }
+
+    #[test]
+    fn parses_poll_options_in_order() {
+        let item = parse_item(POLL_PAGE, "47000001");
+        assert_eq!(item.kind, StoryKind::Poll);
+        assert_eq!(item.points, Some(356));
+
+        let options: Vec<_> = item
+            .poll
+            .iter()
+            .map(|o| (o.id.as_str(), o.text.as_str(), o.points))
+            .collect();
+        assert_eq!(
+            options,
+            [
+                ("47000002", "Rust", Some(1204)),
+                ("47000003", "Go", Some(873)),
+                ("47000004", "Zig & Odin", Some(1)),
+                ("47000005", "Something else", Some(0)),
+            ]
+        );
+
+        // The comment below the options is not one of them.
+        assert_eq!(item.comments.len(), 1);
+        assert_eq!(item.comments[0].text, "Zig, and I have no regrets.");
+    }
+
+    #[test]
+    fn parses_job_title_shapes() {
+        let cases = [
+            (
+                "Acme (YC S21) Is Hiring a Senior Rust Engineer (Remote, US)",
+                (
+                    Some("Acme"),
+                    Some("S21"),
+                    Some("Senior Rust Engineer"),
+                    Some("Remote, US"),
+                ),
+            ),
+            (
+                "Acme (YC S21) Is Hiring a Engineer - Remote (US)",
+                (
+                    Some("Acme"),
+                    Some("S21"),
+                    Some("Engineer"),
+                    Some("Remote (US)"),
+                ),
+            ),
+            (
+                "Acme (YC S21) is hiring an Engineer (Remote - US)",
+                (
+                    Some("Acme"),
+                    Some("S21"),
+                    Some("Engineer"),
+                    Some("Remote - US"),
+                ),
+            ),
+            (
+                "Widgets Inc. (YC W24) is hiring founding engineers – San Francisco",
+                (
+                    Some("Widgets Inc."),
+                    Some("W24"),
+                    Some("founding engineers"),
+                    Some("San Francisco"),
+                ),
+            ),
+            (
+                "Foo Is Hiring Designers in Berlin",
+                (Some("Foo"), None, Some("Designers"), Some("Berlin")),
+            ),
+            (
+                "Bar (YC F25) Is Hiring: Remote",
+                (Some("Bar"), Some("F25"), None, Some("Remote")),
+            ),
+            (
+                "Baz (YC X25) Is Hiring An Account Executive",
+                (Some("Baz"), Some("X25"), Some("Account Executive"), None),
+            ),
+            (
+                "Qux (YC S20) launches its public API",
+                (Some("Qux"), Some("S20"), None, None),
+            ),
+            ("Our engineering blog has moved", (None, None, None, None)),
+        ];
+
+        for (title, expected) in cases {
+            let job = parse_job(Story {
+                title: title.to_string(),
+                ..parse_stories(JOBS_PAGE, 1).remove(0)
+            });
+            assert_eq!(
+                (
+                    job.company.as_deref(),
+                    job.batch.as_deref(),
+                    job.role.as_deref(),
+                    job.location.as_deref()
+                ),
+                expected,
+                "{}",
+                title
+            );
+        }
+    }
+
+    #[test]
+    fn finds_the_more_link() {
+        assert_eq!(
+            more_link(JOBS_PAGE).as_deref(),
+            Some("https://news.ycombinator.com/jobs?next=42000002")
+        );
+    }
+
+    #[test]
+    fn parses_item_header_and_text() {
+        let item = parse_item(ITEM_PAGE, "44000001");
+
+        assert_eq!(item.title, "Ask HN: How do you keep notes & todo lists?");
+        assert_eq!(item.points, Some(87));
+        assert_eq!(item.author.as_deref(), Some("frank"));
+        assert_eq!(item.posted, epoch(1792310400));
+        assert_eq!(
+            item.text.as_deref(),
+            Some(
+                "I have tried plain text files, wikis and three different apps.\n\nWhat works for you, and why?"
+            )
+        );
+        assert!(item.poll.is_empty());
+    }
+
+    #[test]
+    fn parses_comment_tree() {
+        let item = parse_item(ITEM_PAGE, "44000001");
+        let depths: Vec<_> = item.comments.iter().map(|c| c.depth).collect();
+        assert_eq!(depths, [0, 1, 1, 0, 0]);
+
+        let first = &item.comments[0];
+        assert_eq!(first.id, "44000010");
+        assert_eq!(first.author.as_deref(), Some("alice"));
+        assert_eq!(
+            first.text,
+            "Plain text > everything else. I keep one file per project, see https://example.com/a-very-long-path/notes-setup\n\nGrep is my search engine."
+        );
+
+        let last = &item.comments[4];
+        assert!(last.text.contains("  notes/\n    2026-10.md"));
+        assert_eq!(item.ancestors(2)[0].id, "44000010");
+    }
+
+    #[test]
+    fn deleted_comments_have_no_author() {
+        let item = parse_item(ITEM_PAGE, "44000001");
+        let deleted = &item.comments[2];
+
+        assert_eq!(deleted.id, "44000012");
+        assert_eq!(deleted.author, None);
+        assert_eq!(deleted.text, "[deleted]");
+        assert_eq!(deleted.posted, epoch(1792319400));
+    }
+
+    #[test]
+    fn flagged_and_dead_comments_keep_their_text() {
+        let item = parse_item(ITEM_PAGE, "44000001");
+
+        assert_eq!(item.comments[1].author.as_deref(), Some("bob"));
+        assert_eq!(
+            item.comments[1].text,
+            "Everyone who uses plain text is wrong."
+        );
+        assert_eq!(item.comments[3].author.as_deref(), Some("mallory"));
+        assert_eq!(
+            item.comments[3].text,
+            "Check out my note taking app, link in bio."
+        );
+    }
+
+    #[test]
+    fn parses_user_profile() {
+        let user = parse_user(USER_PAGE, "dang").unwrap();
+
+        assert_eq!(user.id, "dang");
+        assert_eq!(user.created, NaiveDate::from_ymd_opt(2010, 10, 5));
+        assert_eq!(user.karma, Some(1_234_567));
+        assert_eq!(
+            user.about.as_deref(),
+            Some(
+                "Hacker News moderator & resident eternal optimist.\n\nEmail: hn@ycombinator.com\n\nSee https://news.ycombinator.com/newsguidelines.html"
+            )
+        );
+        assert_eq!(user.submissions, None);
+    }
+
+    #[test]
+    fn pages_without_profile_fields_are_not_users() {
+        assert!(parse_user(FRONT_PAGE, "nobody").is_none());
+    }
+}
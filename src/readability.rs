@@ -0,0 +1,187 @@
+//! Finds the main content of an article page, readability-style: every
+//! paragraph-like block adds to its parent's and grandparent's score, and the
+//! best scoring container (discounted by how much of it is links) wins.
+
+use crate::model::Article;
+use crate::parse::safe_selector;
+use crate::render;
+use scraper::{ElementRef, Html};
+use std::collections::HashMap;
+
+safe_selector!(block_selector, "p, pre, td, blockquote");
+safe_selector!(anchor_selector, "a");
+
+/// Shorter blocks (captions, bylines, buttons) don't count.
+const MIN_BLOCK_CHARS: usize = 25;
+/// Less extracted text than this means we probably grabbed the wrong part.
+const MIN_ARTICLE_WORDS: usize = 50;
+
+const POSITIVE_HINTS: [&str; 10] = [
+    "article", "body", "content", "entry", "main", "page", "post", "text", "blog", "story",
+];
+const NEGATIVE_HINTS: [&str; 16] = [
+    "comment",
+    "footer",
+    "nav",
+    "sidebar",
+    "sponsor",
+    "share",
+    "related",
+    "menu",
+    "masthead",
+    "widget",
+    "promo",
+    "social",
+    "banner",
+    "cookie",
+    "subscribe",
+    "newsletter",
+];
+/// Blocks inside these are page chrome, never article text.
+const CHROME_TAGS: [&str; 6] = ["nav", "aside", "footer", "header", "form", "script"];
+
+/// Extracts the main text of an article page, or `None` if nothing
+/// article-like was found.
+pub fn extract_article(html: &str, url: &str) -> Option<Article> {
+    let document = Html::parse_document(html);
+    let mut scores = HashMap::new();
+
+    for block in document.select(block_selector()) {
+        if block
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|a| CHROME_TAGS.contains(&a.value().name()))
+        {
+            continue;
+        }
+
+        let text: String = block.text().collect();
+        let chars = text.trim().chars().count();
+        if chars < MIN_BLOCK_CHARS {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (chars / 100).min(3) as f64;
+
+        let Some(parent) = block.parent().and_then(ElementRef::wrap) else {
+            continue;
+        };
+        *scores
+            .entry(parent.id())
+            .or_insert_with(|| initial_score(parent)) += score;
+        if let Some(grandparent) = parent.parent().and_then(ElementRef::wrap) {
+            *scores
+                .entry(grandparent.id())
+                .or_insert_with(|| initial_score(grandparent)) += score / 2.0;
+        }
+    }
+
+    let (best, _) = scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let element = ElementRef::wrap(document.tree.get(id)?)?;
+            Some((element, score * (1.0 - link_density(element))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    let text = render::article_to_text(best);
+    (text.split_whitespace().count() >= MIN_ARTICLE_WORDS).then(|| Article {
+        url: url.to_string(),
+        text,
+    })
+}
+
+/// A container's score before counting its text, from its tag and its
+/// class and id names.
+fn initial_score(element: ElementRef) -> f64 {
+    let mut score: f64 = match element.value().name() {
+        "article" | "main" => 10.0,
+        "div" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+
+    for name in [element.value().attr("class"), element.value().id()]
+        .into_iter()
+        .flatten()
+    {
+        let name = name.to_lowercase();
+        if POSITIVE_HINTS.iter().any(|hint| name.contains(hint)) {
+            score += 25.0;
+        }
+        if NEGATIVE_HINTS.iter().any(|hint| name.contains(hint)) {
+            score -= 25.0;
+        }
+    }
+    score
+}
+
+/// Share of an element's text that sits inside links.
+fn link_density(element: ElementRef) -> f64 {
+    let total: usize = element.text().map(str::len).sum();
+    if total == 0 {
+        return 1.0;
+    }
+    let linked: usize = element
+        .select(anchor_selector())
+        .flat_map(|a| a.text())
+        .map(str::len)
+        .sum();
+    linked as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOG_POST: &str = include_str!("../tests/fixtures/article_blog.html");
+
+    #[test]
+    fn extracts_the_article_body() {
+        let article = extract_article(BLOG_POST, "https://example.com/post").unwrap();
+
+        assert!(article.text.starts_with("Why we rewrote our build system"));
+        assert!(article.text.contains("The old system, written in 2014,"));
+        assert!(article.text.contains("• Builds are hermetic"));
+        assert!(article.text.ends_with("Thanks for reading."));
+    }
+
+    #[test]
+    fn leaves_out_navigation_sidebar_and_comments() {
+        let article = extract_article(BLOG_POST, "https://example.com/post").unwrap();
+
+        for chrome in [
+            "Home",
+            "Subscribe to our newsletter",
+            "Great post",
+            "Copyright",
+        ] {
+            assert!(!article.text.contains(chrome), "found {:?}", chrome);
+        }
+        assert!(!article.text.contains("trackPageView"));
+    }
+
+    #[test]
+    fn keeps_link_texts() {
+        let article = extract_article(BLOG_POST, "https://example.com/post").unwrap();
+
+        assert!(article.text.contains("see the design document for details"));
+        assert!(!article.text.contains("https://example.com/design"));
+    }
+
+    #[test]
+    fn counts_words_and_reading_time() {
+        let article = extract_article(BLOG_POST, "https://example.com/post").unwrap();
+
+        // The fixture's article, heading, byline and list bullets included.
+        assert_eq!(article.word_count(), 180);
+        assert_eq!(article.reading_minutes(), 1);
+    }
+
+    #[test]
+    fn rejects_pages_without_an_article() {
+        let html = "<html><body><nav><a href='/'>Home</a></nav><p>Hello</p></body></html>";
+        assert!(extract_article(html, "https://example.com/").is_none());
+    }
+}
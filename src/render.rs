@@ -0,0 +1,169 @@
+//! Turns HN's small HTML subset (comments, profiles) and extracted article
+//! content into plain text.
+
+use scraper::ElementRef;
+use scraper::node::Node;
+
+/// How links are shown: HN shortens long link texts with "...", so comments
+/// show the href; articles keep their link text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Links {
+    Urls,
+    Text,
+}
+
+/// Tags whose content is never part of the text.
+const SKIPPED_TAGS: [&str; 11] = [
+    "script", "style", "noscript", "template", "nav", "aside", "footer", "form", "button", "svg",
+    "iframe",
+];
+
+/// Tags that start and end a block of their own.
+const BLOCK_TAGS: [&str; 21] = [
+    "div",
+    "section",
+    "article",
+    "main",
+    "header",
+    "figure",
+    "figcaption",
+    "blockquote",
+    "ul",
+    "ol",
+    "dl",
+    "dt",
+    "dd",
+    "table",
+    "tr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+];
+
+/// Renders an element's content as text: paragraphs (`<p>`) become blank
+/// lines, `<pre>` blocks keep their line breaks and links show their full URL.
+pub fn html_to_text(element: ElementRef) -> String {
+    render(element, Links::Urls)
+}
+
+/// Renders article content like `html_to_text`, but keeps link texts.
+/// Headings, lists and other block elements get their own lines, and
+/// scripts, navigation and forms are left out.
+pub fn article_to_text(element: ElementRef) -> String {
+    render(element, Links::Text)
+}
+
+fn render(element: ElementRef, links: Links) -> String {
+    let mut out = String::new();
+    render_children(element, &mut out, links);
+
+    let mut text = String::new();
+    for line in out.lines().map(str::trim_end) {
+        if line.is_empty() && (text.is_empty() || text.ends_with("\n\n")) {
+            continue;
+        }
+        text.push_str(line);
+        text.push('\n');
+    }
+    text.trim().to_string()
+}
+
+fn render_children(element: ElementRef, out: &mut String, links: Links) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => push_collapsed(out, text),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    render_element(child, out, links);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render_element(element: ElementRef, out: &mut String, links: Links) {
+    let name = element.value().name();
+    match name {
+        "p" => {
+            paragraph_break(out);
+            render_children(element, out, links);
+        }
+        "br" => out.push('\n'),
+        "pre" => {
+            paragraph_break(out);
+            for line in element
+                .text()
+                .collect::<String>()
+                .trim_matches('\n')
+                .lines()
+            {
+                out.push_str(line);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        // HN shortens long link texts with "...", the href has the real URL.
+        "a" if links == Links::Urls => match element.value().attr("href") {
+            Some(href) if href.starts_with("http") => push_collapsed(out, href),
+            _ => render_children(element, out, links),
+        },
+        "li" => {
+            line_break(out);
+            out.push_str("• ");
+            render_children(element, out, links);
+            line_break(out);
+        }
+        _ if SKIPPED_TAGS.contains(&name) => {}
+        _ if BLOCK_TAGS.contains(&name) => {
+            paragraph_break(out);
+            render_children(element, out, links);
+            paragraph_break(out);
+        }
+        _ => render_children(element, out, links),
+    }
+}
+
+fn paragraph_break(out: &mut String) {
+    let trimmed = out.trim_end_matches(' ').len();
+    out.truncate(trimmed);
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+}
+
+fn line_break(out: &mut String) {
+    let trimmed = out.trim_end_matches(' ').len();
+    out.truncate(trimmed);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+fn push_collapsed(out: &mut String, text: &str) {
+    if text.starts_with(char::is_whitespace) {
+        push_space(out);
+    }
+
+    let mut words = text.split_whitespace().peekable();
+    let has_words = words.peek().is_some();
+    while let Some(word) = words.next() {
+        out.push_str(word);
+        if words.peek().is_some() {
+            out.push(' ');
+        }
+    }
+
+    if has_words && text.ends_with(char::is_whitespace) {
+        push_space(out);
+    }
+}
+
+fn push_space(out: &mut String) {
+    if !out.is_empty() && !out.ends_with(' ') && !out.ends_with('\n') {
+        out.push(' ');
+    }
+}
@@ -0,0 +1,385 @@
+use crate::http::{self, FetchError, HttpConfig, RateLimiter, RetryPolicy};
+use crate::http_cache::{self, CacheMode, CachedResponse, HttpCache};
+use crate::model::{Article, Comment, Item, Story, User};
+use crate::parse::{self, BASE_URL};
+use crate::readability;
+use anyhow::{Context, Result, bail};
+use chrono::NaiveDate;
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::Client;
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// `whoishiring` posts three threads a month, so this covers about ten years.
+const HIRING_SEARCH_PAGES: usize = 40;
+
+/// Maps a user-facing category name to its HN listing endpoint.
+pub fn category_endpoint(category: &str) -> Option<&'static str> {
+    match category {
+        "top" => Some("news"),
+        "new" => Some("newest"),
+        "best" => Some("best"),
+        "ask" => Some("ask"),
+        "show" => Some("show"),
+        "job" => Some("jobs"),
+        "active" => Some("active"),
+        "noob" => Some("noobstories"),
+        "shownew" => Some("shownew"),
+        "asknew" => Some("asknew"),
+        "pool" => Some("pool"),
+        "launches" => Some("launches"),
+        "classic" => Some("classic"),
+        _ => None,
+    }
+}
+
+/// An item or user HN has no page for, as opposed to a failed request.
+#[derive(Debug)]
+pub struct NotFound(pub String);
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+pub struct HnScraper {
+    client: Client,
+    retry: RetryPolicy,
+    limiter: RateLimiter,
+    cache: HttpCache,
+    /// Age of the oldest response served from disk in offline mode.
+    offline_age: Mutex<Option<Duration>>,
+}
+
+impl HnScraper {
+    pub fn new(config: HttpConfig) -> Result<Self> {
+        let client = Client::builder()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/143.0.0.0 Safari/537.36")
+            .pool_max_idle_per_host(10)
+            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self {
+            client,
+            retry: RetryPolicy::new(config.retries),
+            limiter: RateLimiter::new(config.requests_per_second),
+            cache: HttpCache::new(config.cache_mode),
+            offline_age: Mutex::new(None),
+        })
+    }
+
+    /// How old the data shown in offline mode is, if any came from the cache.
+    pub fn offline_age(&self) -> Option<Duration> {
+        self.offline_age.lock().ok().and_then(|age| *age)
+    }
+
+    async fn try_get(
+        &self,
+        url: &str,
+        cached: Option<&CachedResponse>,
+    ) -> Result<CachedResponse, FetchError> {
+        // Only HN gets rate limited and checked for its throttling page;
+        // articles live on other sites.
+        let is_hn = url.starts_with(BASE_URL);
+        if is_hn {
+            self.limiter.acquire().await?;
+        }
+
+        let mut request = self.client.get(url);
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+
+        if status == StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            return Ok(CachedResponse {
+                fetched_at: http_cache::now_secs(),
+                ..cached.clone()
+            });
+        }
+
+        if !status.is_success() {
+            return Err(FetchError::Status {
+                code: status.as_u16(),
+                retry_after: response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(http::parse_retry_after),
+            });
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+
+        let body = response.text().await?;
+        if is_hn {
+            http::check_throttled(&body)?;
+        }
+
+        Ok(CachedResponse {
+            fetched_at: http_cache::now_secs(),
+            etag,
+            last_modified,
+            body,
+        })
+    }
+
+    /// Fetches a page through the response cache, retrying throttling and
+    /// connection failures with backoff.
+    async fn get_html(&self, url: &str) -> Result<String> {
+        let cached = self.cache.load(url);
+
+        match (self.cache.mode, cached) {
+            (CacheMode::Offline, Some(cached)) => {
+                if let Ok(mut oldest) = self.offline_age.lock() {
+                    *oldest = (*oldest).max(Some(cached.age()));
+                }
+                Ok(cached.body)
+            }
+            (CacheMode::Offline, None) => bail!("{} is not cached and --offline is set", url),
+            (CacheMode::Normal, Some(cached)) if cached.age() < http_cache::ttl_for(url) => {
+                Ok(cached.body)
+            }
+            (_, cached) => {
+                let response = self.get_with_retry(url, cached.as_ref()).await?;
+                // A cache write failure should not hide a successful fetch.
+                let _ = self.cache.store(url, &response);
+                Ok(response.body)
+            }
+        }
+    }
+
+    async fn get_with_retry(
+        &self,
+        url: &str,
+        cached: Option<&CachedResponse>,
+    ) -> Result<CachedResponse> {
+        let mut attempt = 0;
+        loop {
+            match self.try_get(url, cached).await {
+                Ok(response) => return Ok(response),
+                Err(e) => match self.retry.backoff(attempt, &e) {
+                    Some(delay) => {
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => {
+                        return Err(anyhow::Error::new(e))
+                            .with_context(|| format!("Failed to fetch {}", url));
+                    }
+                },
+            }
+        }
+    }
+
+    pub async fn fetch_stories(&self, endpoint: &str, page: usize) -> Result<Vec<Story>> {
+        let url = if page > 1 {
+            let separator = if endpoint.contains('?') { '&' } else { '?' };
+            format!("{}/{}{}p={}", BASE_URL, endpoint, separator, page)
+        } else {
+            format!("{}/{}", BASE_URL, endpoint)
+        };
+
+        let html = self.get_html(&url).await?;
+        let stories = parse::parse_stories(&html, page);
+
+        if stories.is_empty() {
+            bail!(
+                "No stories found on page {}. The page structure may have changed, run `hn doctor` for details.",
+                page
+            );
+        }
+
+        Ok(stories)
+    }
+
+    /// Fetches page `page` of a listing that paginates with "More" links
+    /// (`next=` cursors) rather than `?p=`, walking from the first page.
+    async fn get_listing_page(&self, endpoint: &str, page: usize) -> Result<String> {
+        let mut url = format!("{}/{}", BASE_URL, endpoint);
+        for _ in 1..page {
+            let html = self.get_html(&url).await?;
+            url = parse::more_link(&html)
+                .with_context(|| format!("Page {} is past the end of the listing", page))?;
+        }
+        self.get_html(&url).await
+    }
+
+    /// Fetches one page of a comment listing (`newcomments`, `threads?id=`).
+    pub async fn fetch_comments(&self, endpoint: &str, page: usize) -> Result<Vec<Comment>> {
+        let html = self.get_listing_page(endpoint, page).await?;
+        let comments = parse::parse_comment_list(&html);
+
+        if comments.is_empty() {
+            bail!(
+                "No comments found on page {}. The page structure may have changed, run `hn doctor` for details.",
+                page
+            );
+        }
+
+        Ok(comments)
+    }
+
+    /// Fetches one page of a user's submissions or favorites.
+    pub async fn fetch_user_stories(
+        &self,
+        listing: &str,
+        username: &str,
+        page: usize,
+    ) -> Result<Vec<Story>> {
+        let endpoint = format!("{}?id={}", listing, username);
+        let html = self.get_listing_page(&endpoint, page).await?;
+        let stories = parse::parse_stories(&html, page);
+
+        if stories.is_empty() {
+            bail!("No {} found for '{}' on page {}", listing, username, page);
+        }
+
+        Ok(stories)
+    }
+
+    /// Fetches one page of a user's own comments, each linked to its story.
+    pub async fn fetch_user_comments(&self, username: &str, page: usize) -> Result<Vec<Comment>> {
+        let comments = self
+            .fetch_comments(&format!("threads?id={}", username), page)
+            .await?;
+        // The threads page interleaves replies from other users.
+        Ok(comments
+            .into_iter()
+            .filter(|c| c.author.as_deref() == Some(username))
+            .collect())
+    }
+
+    pub async fn fetch_item(&self, id: &str) -> Result<Item> {
+        let url = format!("{}/item?id={}", BASE_URL, id);
+        let html = self.get_html(&url).await.context("Failed to fetch item")?;
+
+        let item = parse::parse_item(&html, id);
+        if item.title.is_empty() && item.comments.is_empty() {
+            return Err(NotFound(format!("Item '{}' not found", id)).into());
+        }
+
+        Ok(item)
+    }
+
+    /// Fetches an item with the comments from all of its pages; HN splits
+    /// large threads behind "More" links.
+    pub async fn fetch_full_item(&self, id: &str) -> Result<Item> {
+        let url = format!("{}/item?id={}", BASE_URL, id);
+        let mut html = self.get_html(&url).await.context("Failed to fetch item")?;
+
+        let mut item = parse::parse_item(&html, id);
+        if item.title.is_empty() && item.comments.is_empty() {
+            return Err(NotFound(format!("Item '{}' not found", id)).into());
+        }
+
+        while let Some(next) = parse::more_link(&html) {
+            html = self
+                .get_html(&next)
+                .await
+                .with_context(|| format!("Failed to fetch {}", next))?;
+            item.comments.extend(parse::parse_item(&html, id).comments);
+        }
+
+        Ok(item)
+    }
+
+    /// Finds the `whoishiring` thread whose title contains `kind` (lowercase),
+    /// either for `month` or the latest one.
+    pub async fn find_hiring_thread(&self, kind: &str, month: Option<NaiveDate>) -> Result<Story> {
+        let suffix = month.map(|m| m.format("(%B %Y)").to_string());
+
+        // Walk the "More" links ourselves: running out of pages ends the
+        // search, but fetch errors are reported rather than read as "not found".
+        let mut url = format!("{}/submitted?id=whoishiring", BASE_URL);
+        for page in 1..=HIRING_SEARCH_PAGES {
+            let html = self
+                .get_html(&url)
+                .await
+                .context("Failed to fetch the whoishiring submissions")?;
+            let stories = parse::parse_stories(&html, page);
+            if let Some(story) = stories.into_iter().find(|s| {
+                s.title.to_lowercase().contains(kind)
+                    && suffix.as_ref().is_none_or(|sfx| s.title.ends_with(sfx))
+            }) {
+                return Ok(story);
+            }
+            match parse::more_link(&html) {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+
+        match month {
+            Some(month) => bail!("No '{}' thread found for {}", kind, month.format("%B %Y")),
+            None => bail!("No '{}' thread found", kind),
+        }
+    }
+
+    /// Fetches a story's linked page and extracts its main text.
+    pub async fn fetch_article(&self, url: &str) -> Result<Article> {
+        let html = self
+            .get_html(url)
+            .await
+            .context("Failed to fetch article")?;
+        readability::extract_article(&html, url)
+            .with_context(|| format!("Couldn't find the article text on {}", url))
+    }
+
+    /// Fetches the raw HTML of an HN page such as `news` or `item?id=1`.
+    pub async fn fetch_page(&self, path: &str) -> Result<String> {
+        self.get_html(&format!("{}/{}", BASE_URL, path)).await
+    }
+
+    pub async fn fetch_user(&self, username: &str) -> Result<User> {
+        let url = format!("{}/user?id={}", BASE_URL, username);
+        let html = self.get_html(&url).await.context("Failed to fetch user")?;
+
+        match parse::parse_user(&html, username) {
+            Some(user) => Ok(user),
+            None => Err(NotFound(format!(
+                "User '{}' not found or has no public information",
+                username
+            ))
+            .into()),
+        }
+    }
+
+    /// Fetches listing pages with at most `concurrency` requests in flight,
+    /// yielding each page's result in page order as soon as it is ready.
+    pub fn fetch_pages<'a>(
+        &'a self,
+        endpoint: &'a str,
+        pages: impl IntoIterator<Item = usize> + 'a,
+        concurrency: usize,
+    ) -> impl Stream<Item = (usize, Result<Vec<Story>>)> + 'a {
+        stream::iter(pages)
+            .map(move |page| async move { (page, self.fetch_stories(endpoint, page).await) })
+            .buffered(concurrency.max(1))
+    }
+}
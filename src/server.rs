@@ -0,0 +1,289 @@
+//! `hn serve`: a small local HTTP server exposing listings, items and users as JSON.
+
+use crate::http::FetchError;
+use crate::json::{Object, ToJson};
+use crate::scraper::{HnScraper, NotFound, category_endpoint};
+use anyhow::{Context, Result};
+use colored::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+/// How long a client gets to send its request head before it is dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        let mut body = String::new();
+        Object::new().field("error", message).finish(&mut body);
+        Self { status, body }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            503 => "Service Unavailable",
+            _ => "Bad Gateway",
+        }
+    }
+}
+
+enum Route<'a> {
+    Stories { endpoint: &'static str, page: usize },
+    Item(&'a str),
+    User(&'a str),
+}
+
+impl<'a> Route<'a> {
+    fn parse(path: &'a str) -> Result<Self, Response> {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        match segments.as_slice() {
+            ["stories", category] => {
+                let endpoint = category_endpoint(category).ok_or_else(|| {
+                    Response::error(404, &format!("unknown category '{}'", category))
+                })?;
+                let page = match query_param(query, "page").map(str::parse::<usize>) {
+                    None => 1,
+                    Some(Ok(page)) if page > 0 => page,
+                    Some(_) => return Err(Response::error(400, "page must be a positive integer")),
+                };
+                Ok(Route::Stories { endpoint, page })
+            }
+            ["item", id] if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) => {
+                Ok(Route::Item(id))
+            }
+            // HN usernames are letters, digits, '_' and '-'; anything else
+            // could smuggle extra parameters into `user?id=`.
+            ["user", name]
+                if !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') =>
+            {
+                Ok(Route::User(name))
+            }
+            _ => Err(Response::error(404, "unknown route")),
+        }
+    }
+}
+
+struct Server {
+    scraper: HnScraper,
+}
+
+impl Server {
+    async fn respond(&self, method: &str, path: &str) -> Response {
+        if method != "GET" {
+            return Response::error(405, "only GET is supported");
+        }
+        let route = match Route::parse(path) {
+            Ok(route) => route,
+            Err(response) => return response,
+        };
+
+        let result = match route {
+            Route::Stories { endpoint, page } => self
+                .scraper
+                .fetch_stories(endpoint, page)
+                .await
+                .map(|stories| stories.to_json()),
+            Route::Item(id) => self.scraper.fetch_item(id).await.map(|item| item.to_json()),
+            Route::User(name) => self
+                .scraper
+                .fetch_user(name)
+                .await
+                .map(|user| user.to_json()),
+        };
+
+        result.map_or_else(|e| error_response(&e), Response::ok)
+    }
+
+    async fn handle(&self, mut stream: TcpStream) -> Result<()> {
+        let read_head = async {
+            let mut head = Vec::with_capacity(1024);
+            let mut buf = [0u8; 1024];
+            while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await?;
+                if n == 0 || head.len() + n > MAX_REQUEST_HEAD {
+                    break;
+                }
+                head.extend_from_slice(&buf[..n]);
+            }
+            Ok::<_, std::io::Error>(head)
+        };
+        let head = tokio::time::timeout(READ_TIMEOUT, read_head)
+            .await
+            .context("Timed out reading the request")??;
+
+        let head = String::from_utf8_lossy(&head);
+        let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+        let method = request_line.next().unwrap_or_default();
+        let path = request_line.next().unwrap_or("/");
+
+        let response = self.respond(method, path).await;
+
+        let message = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.status,
+            response.reason(),
+            response.body.len(),
+            response.body
+        );
+        stream.write_all(message.as_bytes()).await?;
+        stream.shutdown().await?;
+        Ok(())
+    }
+}
+
+/// Maps a failed fetch to a status: throttling is 503, a missing item or
+/// user 404, anything else a 502 from the upstream site.
+fn error_response(e: &anyhow::Error) -> Response {
+    let status = if matches!(e.downcast_ref(), Some(FetchError::Throttled)) {
+        503
+    } else if e.downcast_ref::<NotFound>().is_some() {
+        404
+    } else {
+        502
+    };
+    Response::error(status, &format!("{:#}", e))
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+pub async fn serve(scraper: HnScraper, bind: &str) -> Result<()> {
+    let listener = TcpListener::bind(bind)
+        .await
+        .with_context(|| format!("Failed to bind {}", bind))?;
+
+    println!(
+        "{} Serving HN as JSON on http://{}",
+        "✓".green(),
+        listener.local_addr()?.to_string().bright_white().bold()
+    );
+    println!(
+        "   {}",
+        "GET /stories/{category}?page=N | /item/{id} | /user/{name}".bright_black()
+    );
+
+    let server = Arc::new(Server { scraper });
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let server = Arc::clone(&server);
+        tokio::spawn(async move {
+            if let Err(e) = server.handle(stream).await {
+                eprintln!("{} {:#}", "Request failed:".red(), e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpConfig;
+    use crate::http_cache::CacheMode;
+
+    fn status(path: &str) -> Option<u16> {
+        Route::parse(path).err().map(|response| response.status)
+    }
+
+    #[test]
+    fn parses_routes() {
+        assert!(matches!(
+            Route::parse("/stories/top"),
+            Ok(Route::Stories {
+                endpoint: "news",
+                page: 1
+            })
+        ));
+        assert!(matches!(
+            Route::parse("/stories/classic?page=3"),
+            Ok(Route::Stories {
+                endpoint: "classic",
+                page: 3
+            })
+        ));
+        assert!(matches!(
+            Route::parse("/item/8863"),
+            Ok(Route::Item("8863"))
+        ));
+        assert!(matches!(
+            Route::parse("/user/dang/"),
+            Ok(Route::User("dang"))
+        ));
+        assert!(matches!(
+            Route::parse("/user/some_one-2"),
+            Ok(Route::User("some_one-2"))
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_routes() {
+        assert_eq!(status("/stories/top?page=0"), Some(400));
+        assert_eq!(status("/stories/top?page=two"), Some(400));
+        assert_eq!(status("/stories/nope"), Some(404));
+        assert_eq!(status("/item/12ab"), Some(404));
+        assert_eq!(status("/item/"), Some(404));
+        assert_eq!(status("/user/a&id=b"), Some(404));
+        assert_eq!(status("/user/a%26id=b"), Some(404));
+        assert_eq!(status("/"), Some(404));
+    }
+
+    #[test]
+    fn maps_errors_to_statuses() {
+        let throttled = anyhow::Error::new(FetchError::Throttled).context("Failed to fetch item");
+        assert_eq!(error_response(&throttled).status, 503);
+
+        let missing = anyhow::Error::new(NotFound("Item '1' not found".to_string()));
+        assert_eq!(error_response(&missing).status, 404);
+
+        let other = anyhow::Error::new(FetchError::Status {
+            code: 500,
+            retry_after: None,
+        });
+        let response = error_response(&other);
+        assert_eq!((response.status, response.reason()), (502, "Bad Gateway"));
+        assert_eq!(response.body, r#"{"error":"HTTP status 500"}"#);
+    }
+
+    #[tokio::test]
+    async fn only_answers_get() {
+        let server = Server {
+            scraper: HnScraper::new(HttpConfig {
+                retries: 0,
+                requests_per_second: 0.0,
+                cache_mode: CacheMode::Offline,
+            })
+            .unwrap(),
+        };
+        let response = server.respond("POST", "/item/1").await;
+        assert_eq!(
+            (response.status, response.reason()),
+            (405, "Method Not Allowed")
+        );
+        assert_eq!(server.respond("GET", "/nowhere").await.status, 404);
+    }
+}